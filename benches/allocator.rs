@@ -0,0 +1,86 @@
+//! Benchmarks for workloads dominated by allocations in the C runtime: parsing skeleton files,
+//! and clipping, which allocates every frame. The threaded benchmarks run the same work on
+//! separate skeletons at once, which should scale with the number of threads.
+//!
+//! Results on a single core (4.1 runtime), before and after replacing the allocator's
+//! mutex-guarded `HashMap` of allocations with a size header on each allocation:
+//!
+//! | Benchmark            | `HashMap` | Size header |
+//! |----------------------|-----------|-------------|
+//! | `load_skeleton_json` | 3.92 ms   | 2.87 ms     |
+//! | `clipping/1`         | 1.59 ms   | 1.52 ms     |
+//! | `clipping/4`         | 6.34 ms   | 6.11 ms     |
+//!
+//! With a single core, the threaded benchmark cannot show how either allocator scales. The size
+//! header removes the lock, but every allocation still updates the shared atomic counters in
+//! `rusty_spine::memory`, so `clipping/4` needs to be measured on several cores before claiming
+//! it scales.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rusty_spine::{
+    controller::SkeletonController, AnimationStateData, Atlas, SkeletonData, SkeletonJson,
+};
+
+#[cfg(not(feature = "spine38"))]
+const SPINEBOY_DIR: &str = "assets/spineboy/export";
+#[cfg(feature = "spine38")]
+const SPINEBOY_DIR: &str = "assets/spineboy-3.8/export";
+
+fn load_spineboy() -> (Arc<SkeletonData>, Arc<AnimationStateData>) {
+    let atlas = Arc::new(Atlas::new_from_file(format!("{SPINEBOY_DIR}/spineboy.atlas")).unwrap());
+    let skeleton_data = Arc::new(
+        SkeletonJson::new(atlas)
+            .read_skeleton_data_file(format!("{SPINEBOY_DIR}/spineboy-pro.json"))
+            .unwrap(),
+    );
+    let animation_state_data = Arc::new(AnimationStateData::new(skeleton_data.clone()));
+    (skeleton_data, animation_state_data)
+}
+
+/// Play the portal animation, which uses a clipping attachment, for a number of frames.
+fn play_portal(skeleton_data: &Arc<SkeletonData>, animation_state_data: &Arc<AnimationStateData>) {
+    let mut controller =
+        SkeletonController::new(skeleton_data.clone(), animation_state_data.clone());
+    controller
+        .animation_state
+        .set_animation_by_name(0, "portal", true)
+        .unwrap();
+    for _ in 0..60 {
+        controller.update(1. / 60.);
+        criterion::black_box(controller.combined_renderables());
+    }
+}
+
+fn load(c: &mut Criterion) {
+    c.bench_function("load_skeleton_json", |b| b.iter(load_spineboy));
+}
+
+fn clipping(c: &mut Criterion) {
+    let (skeleton_data, animation_state_data) = load_spineboy();
+    let mut group = c.benchmark_group("clipping");
+    for threads in [1, 4] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    std::thread::scope(|scope| {
+                        for _ in 0..threads {
+                            scope.spawn(|| play_portal(&skeleton_data, &animation_state_data));
+                        }
+                    });
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = load, clipping
+}
+criterion_main!(benches);
@@ -51,10 +51,20 @@ fn main() -> anyhow::Result<()> {
 
     let sh = Shell::new()?;
     if what_to_run.contains(Check::CHECK) {
-        check(&sh, Target::Default, Features(&["libc", "mint"]))?;
+        check(
+            &sh,
+            Target::Default,
+            Features(&["libc", "mint"]),
+            OptionalFeatures(&["rayon", "serde", "texture_packer", "zip"]),
+        )?;
     }
     if what_to_run.contains(Check::WASM_CHECK) {
-        check(&sh, Target::Wasm, Features(&["mint"]))?;
+        check(
+            &sh,
+            Target::Wasm,
+            Features(&["mint"]),
+            OptionalFeatures(&[]),
+        )?;
     }
     if what_to_run.contains(Check::EXAMPLE_CHECK) {
         example_check(&sh)?;
@@ -63,7 +73,11 @@ fn main() -> anyhow::Result<()> {
         fmt(&sh)?;
     }
     if what_to_run.contains(Check::TEST) {
-        test(&sh, Features(&["libc", "mint"]))?;
+        test(
+            &sh,
+            Features(&["libc", "mint"]),
+            OptionalFeatures(&["rayon", "serde", "texture_packer", "zip"]),
+        )?;
     }
     if what_to_run.contains(Check::DOC_TEST) {
         doc_test(&sh)?;
@@ -77,10 +91,18 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn check(sh: &Shell, target: Target, features: Features) -> anyhow::Result<()> {
+fn check(
+    sh: &Shell,
+    target: Target,
+    features: Features,
+    optional_features: OptionalFeatures,
+) -> anyhow::Result<()> {
     let target_flags = &target.flags();
     let feature_combination_flags = features.combination_flags();
-    for feature_flags in feature_combination_flags.iter() {
+    for feature_flags in feature_combination_flags
+        .iter()
+        .chain(optional_features.flags().iter())
+    {
         cmd!(sh, "cargo check {target_flags...} {feature_flags...}").run()?;
     }
     Ok(())
@@ -96,9 +118,12 @@ fn fmt(sh: &Shell) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn test(sh: &Shell, features: Features) -> anyhow::Result<()> {
+fn test(sh: &Shell, features: Features, optional_features: OptionalFeatures) -> anyhow::Result<()> {
     let feature_combination_flags = features.combination_flags();
-    for feature_flags in feature_combination_flags.iter() {
+    for feature_flags in feature_combination_flags
+        .iter()
+        .chain(optional_features.flags().iter())
+    {
         cmd!(
             sh,
             "cargo test --workspace --lib --bins --tests {feature_flags...}"
@@ -38,3 +38,22 @@ impl Features {
         feature_combinations
     }
 }
+
+/// Optional features, which are each checked once on top of the default features, and then all
+/// together with every other feature.
+pub struct OptionalFeatures(pub &'static [&'static str]);
+
+impl OptionalFeatures {
+    pub fn flags(&self) -> Vec<Vec<String>> {
+        if self.0.is_empty() {
+            return vec![];
+        }
+        let mut feature_flags: Vec<Vec<String>> = self
+            .0
+            .iter()
+            .map(|feature| vec!["--features".to_owned(), (*feature).to_owned()])
+            .collect();
+        feature_flags.push(vec!["--all-features".to_owned()]);
+        feature_flags
+    }
+}
@@ -197,12 +197,11 @@ struct Spine {
 
 impl Spine {
     pub fn load(info: SpineDemo) -> Self {
-        // Load atlas and auto-detect if the textures are premultiplied
+        // Load atlas
         let atlas = Arc::new(
             Atlas::new_from_file(info.atlas_path)
                 .unwrap_or_else(|_| panic!("failed to load atlas file: {}", info.atlas_path)),
         );
-        let premultiplied_alpha = atlas.pages().any(|page| page.pma());
 
         // Load either binary or json skeleton files
         let skeleton_data = Arc::new(match info.skeleton_path {
@@ -231,9 +230,12 @@ impl Spine {
         // Use of this helper is not required but it does handle a lot of little things for you.
         let mut controller = SkeletonController::new(skeleton_data, animation_state_data)
             .with_settings(SkeletonControllerSettings {
-                premultiplied_alpha,
+                // Auto-detect if the textures are premultiplied from the atlas pages
+                premultiplied_alpha_from_atlas: true,
+                skip_pending_textures: false,
                 cull_direction: CullDirection::CounterClockwise,
                 color_space: ColorSpace::SRGB,
+                ..Default::default()
             });
 
         // Listen for animation events
@@ -295,7 +297,6 @@ impl Spine {
                 .unwrap_or_else(|_| panic!("failed to set skin: {skin}"));
         }
 
-        controller.settings.premultiplied_alpha = premultiplied_alpha;
         Self {
             controller,
             world: Mat4::from_translation(info.position.extend(0.))
@@ -593,7 +594,7 @@ impl EventHandler for Stage {
                 color_blend,
             } = renderable
                 .blend_mode
-                .get_blend_states(self.spine.controller.settings.premultiplied_alpha);
+                .get_blend_states(renderable.premultiplied_alpha);
             ctx.set_blend(Some(color_blend), Some(alpha_blend));
 
             // Create the vertex and index buffers for miniquad
@@ -1,6 +1,14 @@
 use crate::{
-    c::spAnimation,
+    c::{spAnimation, spAnimation_apply, spMixBlend, spMixDirection},
     c_interface::{NewFromPtr, SyncPtr},
+    Skeleton,
+};
+
+#[cfg(not(feature = "spine38"))]
+use crate::{
+    c::spTimeline,
+    c_interface::{CTmpPtrIterator, CTmpRef},
+    timeline::Timeline,
 };
 
 /// Stores timelines for animating a skeleton.
@@ -34,5 +42,96 @@ impl Animation {
         f32
     );
     c_ptr!(c_animation, spAnimation);
-    // TODO: timeline accessors
+
+    /// Applies the animation's timelines to the specified skeleton.
+    ///
+    /// Events fired by the animation are not collected, use an
+    /// [`AnimationState`](`crate::AnimationState`) if events are needed.
+    ///
+    /// See [`Animation::apply`](http://esotericsoftware.com/spine-api-reference#Animation-apply).
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        &self,
+        skeleton: &mut Skeleton,
+        last_time: f32,
+        time: f32,
+        looping: bool,
+        alpha: f32,
+        blend: MixBlend,
+        direction: MixDirection,
+    ) {
+        unsafe {
+            spAnimation_apply(
+                self.c_ptr(),
+                skeleton.c_ptr(),
+                last_time,
+                time,
+                i32::from(looping),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                alpha,
+                blend as spMixBlend,
+                direction as spMixDirection,
+            );
+        }
+    }
+}
+
+#[cfg(not(feature = "spine38"))]
+impl Animation {
+    /// The number of timelines in this animation.
+    #[must_use]
+    pub fn timelines_count(&self) -> usize {
+        unsafe { (*self.c_ptr_ref().timelines).size as usize }
+    }
+
+    /// The timelines keying the properties animated by this animation.
+    #[must_use]
+    pub fn timelines(&self) -> CTmpPtrIterator<'_, Animation, Timeline, spTimeline> {
+        CTmpPtrIterator::new(
+            self,
+            unsafe { (*self.c_ptr_ref().timelines).items },
+            self.timelines_count(),
+        )
+    }
+
+    #[must_use]
+    pub fn timeline_at_index(&self, index: usize) -> Option<CTmpRef<'_, Animation, Timeline>> {
+        if index < self.timelines_count() {
+            Some(CTmpRef::new(self, unsafe {
+                Timeline::new_from_ptr(*(*self.c_ptr_ref().timelines).items.add(index))
+            }))
+        } else {
+            None
+        }
+    }
+}
+
+/// Controls how timeline values are mixed with the pose when applying an animation.
+///
+/// [Spine API Reference](http://esotericsoftware.com/spine-api-reference#MixBlend)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MixBlend {
+    /// Transitions from the setup value to the timeline value.
+    Setup = 0,
+    /// Transitions from the current value to the timeline value. Before the first frame, the
+    /// setup value is set.
+    First = 1,
+    /// Transitions from the current value to the timeline value. No change is made before the
+    /// first frame.
+    Replace = 2,
+    /// Transitions from the current value to the current value plus the timeline value.
+    Add = 3,
+}
+
+/// Indicates whether a timeline's alpha is mixing out over time toward 0 (the setup or current
+/// pose value) or mixing in toward 1 (the timeline's value).
+///
+/// [Spine API Reference](http://esotericsoftware.com/spine-api-reference#MixDirection)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MixDirection {
+    In = 0,
+    Out = 1,
 }
@@ -1,4 +1,12 @@
-use std::sync::Arc;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Once,
+    },
+};
 
 use crate::{
     animation::Animation,
@@ -9,19 +17,24 @@ use crate::{
         spAnimationState_apply, spAnimationState_clearListenerNotifications,
         spAnimationState_clearTrack, spAnimationState_clearTracks, spAnimationState_create,
         spAnimationState_dispose, spAnimationState_disposeStatics, spAnimationState_getCurrent,
-        spAnimationState_setAnimation, spAnimationState_setAnimationByName,
-        spAnimationState_setEmptyAnimation, spAnimationState_setEmptyAnimations,
-        spAnimationState_update, spEvent, spEventType, spTrackEntry, spTrackEntry_getAnimationTime,
+        spAnimationState_initStatics, spAnimationState_setAnimation,
+        spAnimationState_setAnimationByName, spAnimationState_setEmptyAnimation,
+        spAnimationState_setEmptyAnimations, spAnimationState_update, spEvent, spEventType,
+        spTrackEntry, spTrackEntry_getAnimationTime,
     },
-    c_interface::{to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
+    c_interface::{new_handle_id, to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     error::SpineError,
     event::Event,
+    memory::{self, MemoryCategory},
     skeleton::Skeleton,
     AnimationEvent,
 };
 
 #[cfg(not(feature = "spine38"))]
-use crate::c::{spAnimationState_clearNext, spTrackEntry_getTrackComplete};
+use crate::{
+    c::{_spAnimationState, spAnimationState_clearNext, spTrackEntry_getTrackComplete},
+    track_mask::{MaskedAnimation, TrackMask, TrackMaskState},
+};
 
 /// Applies animations over time, queues animations for later playback, mixes (crossfading) between
 /// animations, and applies multiple animations on top of each other (layering).
@@ -34,7 +47,9 @@ use crate::c::{spAnimationState_clearNext, spTrackEntry_getTrackComplete};
 pub struct AnimationState {
     c_animation_state: SyncPtr<spAnimationState>,
     owns_memory: bool,
+    handle_id: u64,
     _animation_state_data: Option<Arc<AnimationStateData>>,
+    _not_sync: PhantomData<Cell<()>>,
 }
 
 impl NewFromPtr<spAnimationState> for AnimationState {
@@ -42,7 +57,13 @@ impl NewFromPtr<spAnimationState> for AnimationState {
         Self {
             c_animation_state: SyncPtr(c_animation_state),
             owns_memory: false,
+            handle_id: (*c_animation_state)
+                .userData
+                .cast::<AnimationStateUserData>()
+                .as_ref()
+                .map_or(0, |user_data| user_data.handle_id),
             _animation_state_data: None,
+            _not_sync: PhantomData,
         }
     }
 }
@@ -50,15 +71,31 @@ impl NewFromPtr<spAnimationState> for AnimationState {
 impl AnimationState {
     #[must_use]
     pub fn new(animation_state_data: Arc<AnimationStateData>) -> Self {
-        let c_animation_state = unsafe { spAnimationState_create(animation_state_data.c_ptr()) };
+        // The empty animation is shared by every animation state. Create it once, so animation
+        // states created on several threads at the same time do not race to create it.
+        static EMPTY_ANIMATION: Once = Once::new();
+        EMPTY_ANIMATION.call_once(|| {
+            memory::runtime_statics(|| unsafe { spAnimationState_initStatics() });
+        });
+        let c_animation_state = memory::with_category(MemoryCategory::AnimationState, || unsafe {
+            spAnimationState_create(animation_state_data.c_ptr())
+        });
+        let handle_id = new_handle_id();
+        let user_data = AnimationStateUserData {
+            handle_id,
+            ..Default::default()
+        };
         unsafe {
             (*c_animation_state).userData =
-                (Box::leak(Box::default()) as *mut AnimationStateUserData).cast::<c_void>();
+                (Box::leak(Box::new(user_data)) as *mut AnimationStateUserData).cast::<c_void>();
+            (*c_animation_state).listener = Some(c_listener);
         }
         Self {
             c_animation_state: SyncPtr(c_animation_state),
             owns_memory: true,
+            handle_id,
             _animation_state_data: Some(animation_state_data),
+            _not_sync: PhantomData,
         }
     }
 
@@ -69,7 +106,118 @@ impl AnimationState {
     }
 
     pub fn apply(&self, skeleton: &mut Skeleton) -> bool {
-        unsafe { spAnimationState_apply(self.c_animation_state.0, skeleton.c_ptr()) != 0 }
+        #[cfg(not(feature = "spine38"))]
+        let masked = unsafe { self.swap_masked_animations() };
+        let applied =
+            unsafe { spAnimationState_apply(self.c_animation_state.0, skeleton.c_ptr()) != 0 };
+        #[cfg(not(feature = "spine38"))]
+        for (c_track_entry, c_animation) in masked {
+            unsafe {
+                (*c_track_entry).animation = c_animation;
+            }
+        }
+        applied
+    }
+
+    /// Point the track entries of all masked tracks (including the entries they are mixing
+    /// from) at masked copies of their animations, returning the original animations so they
+    /// can be restored after applying.
+    #[cfg(not(feature = "spine38"))]
+    unsafe fn swap_masked_animations(&self) -> Vec<(*mut spTrackEntry, *mut spAnimation)> {
+        let Some(user_data) = (*self.c_ptr())
+            .userData
+            .cast::<AnimationStateUserData>()
+            .as_ref()
+        else {
+            return vec![];
+        };
+        let mut masked = vec![];
+        for (track_index, track_mask) in &user_data.track_masks {
+            if *track_index >= self.tracks_count() {
+                continue;
+            }
+            let mut animations = track_mask.animations.lock().unwrap();
+            let mut c_track_entry = *(*self.c_ptr()).tracks.add(*track_index);
+            while !c_track_entry.is_null() {
+                let c_animation = (*c_track_entry).animation;
+                let masked_animation = animations
+                    .entry(c_animation)
+                    .or_insert_with(|| MaskedAnimation::new(c_animation, &track_mask.resolved));
+                (*c_track_entry).animation = masked_animation.c_ptr();
+                masked.push((c_track_entry, c_animation));
+                c_track_entry = (*c_track_entry).mixingFrom;
+            }
+        }
+        masked
+    }
+
+    /// Restrict the bones and slots affected by the animations on a track, see [`TrackMask`].
+    ///
+    /// The mask is kept when animations on the track change, and replaces any previous mask on
+    /// the track.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if the mask refers to a bone or slot which doesn't exist.
+    #[cfg(not(feature = "spine38"))]
+    pub fn set_track_mask(
+        &mut self,
+        track_index: usize,
+        mask: &TrackMask,
+    ) -> Result<(), SpineError> {
+        let resolved = mask.resolve(&self.data().skeleton_data())?;
+        let user_data = unsafe {
+            &mut *((*self.c_animation_state.0)
+                .userData
+                .cast::<AnimationStateUserData>())
+        };
+        user_data.track_masks.insert(
+            track_index,
+            TrackMaskState {
+                mask: mask.clone(),
+                resolved,
+                animations: Mutex::new(HashMap::new()),
+            },
+        );
+        self.mark_animations_changed();
+        Ok(())
+    }
+
+    /// Remove the mask from a track, see [`AnimationState::set_track_mask`].
+    #[cfg(not(feature = "spine38"))]
+    pub fn clear_track_mask(&mut self, track_index: usize) {
+        let user_data = unsafe {
+            &mut *((*self.c_animation_state.0)
+                .userData
+                .cast::<AnimationStateUserData>())
+        };
+        if user_data.track_masks.remove(&track_index).is_some() {
+            self.mark_animations_changed();
+        }
+    }
+
+    /// Masking replaces the timelines of a track's animations, so the timeline modes computed for
+    /// mixing need to be recomputed on the next apply.
+    #[cfg(not(feature = "spine38"))]
+    fn mark_animations_changed(&mut self) {
+        unsafe {
+            (*self.c_ptr().cast::<_spAnimationState>()).animationsChanged = 1;
+        }
+    }
+
+    /// The mask of a track, see [`AnimationState::set_track_mask`].
+    #[cfg(not(feature = "spine38"))]
+    #[must_use]
+    pub fn track_mask(&self, track_index: usize) -> Option<&TrackMask> {
+        let user_data = unsafe {
+            &*((*self.c_animation_state.0)
+                .userData
+                .cast::<AnimationStateUserData>())
+        };
+        user_data
+            .track_masks
+            .get(&track_index)
+            .map(|track_mask| &track_mask.mask)
     }
 
     /// Clears all animations in all track entries in this animation state.
@@ -115,6 +263,30 @@ impl AnimationState {
     /// Sets the animation for the given track by name, clearing any queued tracks, and returning
     /// the track index. If the track index doesn't exist then it will be created.
     ///
+    /// The returned track entry borrows the animation state, since updating it may dispose of the
+    /// entry. Use [`TrackEntryHandle`] to refer to it across updates:
+    ///
+    /// ```compile_fail
+    /// # #[path="./test.rs"]
+    /// # mod test;
+    /// # let (_, mut animation_state) = test::TestAsset::spineboy().instance();
+    /// let track_entry = animation_state.set_animation_by_name(0, "run", false).unwrap();
+    /// animation_state.update(10.);
+    /// println!("{}", track_entry.track_time());
+    /// ```
+    ///
+    /// ```
+    /// # #[path="./test.rs"]
+    /// # mod test;
+    /// # let (_, mut animation_state) = test::TestAsset::spineboy().instance();
+    /// let track_entry = animation_state.set_animation_by_name(0, "run", false).unwrap();
+    /// let track_entry_handle = track_entry.handle();
+    /// animation_state.update(10.);
+    /// if let Some(track_entry) = track_entry_handle.get(&animation_state) {
+    ///     println!("{}", track_entry.track_time());
+    /// }
+    /// ```
+    ///
     /// # Errors
     ///
     /// Returns [`SpineError::NotFound`] if an animation doesn't exist with the given name.
@@ -294,6 +466,9 @@ impl AnimationState {
     /// Set the event listener on this animation state. An animation state can only have one event
     /// listener at a time.
     ///
+    /// The listener is called on whichever thread updates the animation state, so it must be
+    /// [`Send`]. See [Threading](`crate#threading`).
+    ///
     /// ```
     /// # #[path="./test.rs"]
     /// # mod test;
@@ -343,63 +518,8 @@ impl AnimationState {
     /// ```
     pub fn set_listener<F>(&mut self, listener: F)
     where
-        F: Fn(&AnimationState, AnimationEvent) + 'static,
+        F: Fn(&AnimationState, AnimationEvent) + Send + 'static,
     {
-        extern "C" fn c_listener(
-            c_animation_state: *mut spAnimationState,
-            c_event_type: spEventType,
-            c_track_entry: *mut spTrackEntry,
-            c_event: *mut spEvent,
-        ) {
-            let user_data = unsafe {
-                &mut *((*c_animation_state)
-                    .userData
-                    .cast::<AnimationStateUserData>())
-            };
-            if let Some(listener) = &user_data.listener {
-                let animation_state = unsafe { AnimationState::new_from_ptr(c_animation_state) };
-                let track_entry = unsafe { TrackEntry::new_from_ptr(c_track_entry) };
-                let event_type = EventType::from(c_event_type);
-                match event_type {
-                    EventType::Start => {
-                        listener(&animation_state, AnimationEvent::Start { track_entry });
-                    }
-                    EventType::Interrupt => {
-                        listener(&animation_state, AnimationEvent::Interrupt { track_entry });
-                    }
-                    EventType::End => {
-                        listener(&animation_state, AnimationEvent::End { track_entry });
-                    }
-                    EventType::Complete => {
-                        listener(&animation_state, AnimationEvent::Complete { track_entry });
-                    }
-                    EventType::Dispose => {
-                        listener(&animation_state, AnimationEvent::Dispose { track_entry });
-                    }
-                    EventType::Event => {
-                        assert!(!c_event.is_null());
-                        let event = unsafe { Event::new_from_ptr(c_event) };
-                        let raw_event = unsafe { Event::new_from_ptr(c_event) };
-                        listener(
-                            &animation_state,
-                            AnimationEvent::Event {
-                                track_entry,
-                                name: event.data().name(),
-                                time: event.time(),
-                                int: event.int_value(),
-                                float: event.float_value(),
-                                string: event.string_value(),
-                                audio_path: event.data().audio_path(),
-                                volume: event.volume(),
-                                balance: event.balance(),
-                                event: raw_event,
-                            },
-                        );
-                    }
-                    EventType::Unknown => {}
-                };
-            }
-        }
         let user_data = unsafe {
             &mut *((*self.c_animation_state.0)
                 .userData
@@ -462,6 +582,29 @@ impl AnimationState {
             spAnimationState_disposeStatics();
         }
     }
+    /// The id checked by handles to this object, so they are not valid for another object at the
+    /// same address.
+    pub(crate) const fn handle_id(&self) -> u64 {
+        self.handle_id
+    }
+
+    /// The id of a track entry in this animation state checked by [`TrackEntryHandle`], assigned
+    /// the first time a handle is created for it.
+    fn track_entry_id(&self, c_track_entry: *const spTrackEntry) -> u64 {
+        static NEXT_TRACK_ENTRY_ID: AtomicU64 = AtomicU64::new(1);
+        let user_data = unsafe {
+            &*((*self.c_animation_state.0)
+                .userData
+                .cast::<AnimationStateUserData>())
+        };
+        *user_data
+            .track_entry_ids
+            .lock()
+            .unwrap()
+            .entry(c_track_entry as usize)
+            .or_insert_with(|| NEXT_TRACK_ENTRY_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
     c_ptr!(c_animation_state, spAnimationState);
 }
 
@@ -483,17 +626,92 @@ impl Drop for AnimationState {
     }
 }
 
-type AnimationStateListenerCb = Box<dyn Fn(&AnimationState, AnimationEvent)>;
+extern "C" fn c_listener(
+    c_animation_state: *mut spAnimationState,
+    c_event_type: spEventType,
+    c_track_entry: *mut spTrackEntry,
+    c_event: *mut spEvent,
+) {
+    let user_data = unsafe {
+        &*((*c_animation_state)
+            .userData
+            .cast::<AnimationStateUserData>())
+    };
+    let event_type = EventType::from(c_event_type);
+    if let Some(listener) = &user_data.listener {
+        let animation_state = unsafe { AnimationState::new_from_ptr(c_animation_state) };
+        let track_entry = CTmpRef::new(&animation_state, unsafe {
+            TrackEntry::new_from_ptr(c_track_entry)
+        });
+        match event_type {
+            EventType::Start => {
+                listener(&animation_state, AnimationEvent::Start { track_entry });
+            }
+            EventType::Interrupt => {
+                listener(&animation_state, AnimationEvent::Interrupt { track_entry });
+            }
+            EventType::End => {
+                listener(&animation_state, AnimationEvent::End { track_entry });
+            }
+            EventType::Complete => {
+                listener(&animation_state, AnimationEvent::Complete { track_entry });
+            }
+            EventType::Dispose => {
+                listener(&animation_state, AnimationEvent::Dispose { track_entry });
+            }
+            EventType::Event => {
+                assert!(!c_event.is_null());
+                let event = unsafe { Event::new_from_ptr(c_event) };
+                let raw_event =
+                    CTmpRef::new(&animation_state, unsafe { Event::new_from_ptr(c_event) });
+                listener(
+                    &animation_state,
+                    AnimationEvent::Event {
+                        track_entry,
+                        name: event.data().name(),
+                        time: event.time(),
+                        int: event.int_value(),
+                        float: event.float_value(),
+                        string: event.string_value(),
+                        audio_path: event.data().audio_path(),
+                        volume: event.volume(),
+                        balance: event.balance(),
+                        event: raw_event,
+                    },
+                );
+            }
+            EventType::Unknown => {}
+        };
+    }
+    if event_type == EventType::Dispose {
+        user_data
+            .track_entry_ids
+            .lock()
+            .unwrap()
+            .remove(&(c_track_entry as usize));
+    }
+}
+
+type AnimationStateListenerCb = Box<dyn Fn(&AnimationState, AnimationEvent) + Send>;
 
 #[derive(Default)]
 struct AnimationStateUserData {
+    /// The id of the animation state, so wrappers created from its pointer are checked by handles
+    /// like the animation state itself.
+    handle_id: u64,
     listener: Option<AnimationStateListenerCb>,
+    /// Ids of the track entries handles were created for, removed when the entry is disposed so
+    /// handles are not valid for another entry allocated at the same address.
+    track_entry_ids: Mutex<HashMap<usize, u64>>,
+    #[cfg(not(feature = "spine38"))]
+    track_masks: HashMap<usize, TrackMaskState>,
 }
 
 /// The variants of event types.
 ///
 /// Usually not necessary to check, instead use the variants of [`AnimationEvent`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventType {
     Start = 0,
     Interrupt = 1,
@@ -567,7 +785,18 @@ impl TrackEntry {
         if handle.index < track_count {
             let track_at_index =
                 unsafe { *(*handle.c_parent.0).tracks.offset(handle.index as isize) };
+            let user_data = unsafe {
+                &*((*handle.c_parent.0)
+                    .userData
+                    .cast::<AnimationStateUserData>())
+            };
             track_at_index == handle.c_item.0
+                && user_data
+                    .track_entry_ids
+                    .lock()
+                    .unwrap()
+                    .get(&(handle.c_item.0 as usize))
+                    == Some(&handle.item_id)
         } else {
             false
         }
@@ -890,14 +1119,26 @@ c_handle_indexed_decl!(
 impl<'a> CTmpRef<'a, AnimationState, TrackEntry> {
     #[must_use]
     pub fn handle(&self) -> TrackEntryHandle {
-        TrackEntryHandle::new(self.track_index() as i32, self.c_ptr(), self.parent.c_ptr())
+        TrackEntryHandle::new(
+            self.track_index() as i32,
+            self.c_ptr(),
+            self.parent.c_ptr(),
+            self.parent.handle_id(),
+            self.parent.track_entry_id(self.c_ptr()),
+        )
     }
 }
 
 impl<'a> CTmpMut<'a, AnimationState, TrackEntry> {
     #[must_use]
     pub fn handle(&self) -> TrackEntryHandle {
-        TrackEntryHandle::new(self.track_index() as i32, self.c_ptr(), self.parent.c_ptr())
+        TrackEntryHandle::new(
+            self.track_index() as i32,
+            self.c_ptr(),
+            self.parent.c_ptr(),
+            self.parent.handle_id(),
+            self.parent.track_entry_id(self.c_ptr()),
+        )
     }
 }
 
@@ -905,6 +1146,8 @@ impl<'a> CTmpMut<'a, AnimationState, TrackEntry> {
 mod tests {
     use crate::test::TestAsset;
 
+    use super::*;
+
     #[test]
     fn track_entry_optional() {
         let (_, mut animation_state) = TestAsset::spineboy().instance();
@@ -949,4 +1192,77 @@ mod tests {
         let _ = animation_state.set_animation_by_name(0, "run", true);
         assert!(track_handle.get(&animation_state).is_none());
     }
+
+    /// A handle must not resolve to a new track entry allocated at the address of a disposed one.
+    #[test]
+    fn track_entry_invalidate_dispose() {
+        let (_, mut animation_state) = TestAsset::spineboy().instance();
+        let _ = animation_state.set_animation_by_name(0, "idle", true);
+        let track_handle = animation_state.track_at_index(0).unwrap().handle();
+        animation_state.clear_track(0);
+        let _ = animation_state.set_animation_by_name(0, "idle", true);
+        assert!(track_handle.get(&animation_state).is_none());
+
+        // A handle to the disposed entry, as if the new entry was allocated at its address.
+        let new_track_handle = animation_state.track_at_index(0).unwrap().handle();
+        let disposed_track_handle = TrackEntryHandle::new(
+            0,
+            new_track_handle.c_item.0,
+            animation_state.c_ptr(),
+            animation_state.handle_id(),
+            track_handle.item_id,
+        );
+        assert!(disposed_track_handle.get(&animation_state).is_none());
+        assert!(new_track_handle.get(&animation_state).is_some());
+    }
+
+    #[test]
+    fn track_entry_listener_dispose() {
+        let (_, mut animation_state) = TestAsset::spineboy().instance();
+        let disposed = Arc::new(Mutex::new(vec![]));
+        let disposed_clone = disposed.clone();
+        animation_state.set_listener(move |_, animation_event| {
+            if let AnimationEvent::Dispose { track_entry } = animation_event {
+                disposed_clone
+                    .lock()
+                    .unwrap()
+                    .push(track_entry.animation().name().to_owned());
+            }
+        });
+        let _ = animation_state.set_animation_by_name(0, "idle", true);
+        let track_handle = animation_state.track_at_index(0).unwrap().handle();
+        animation_state.clear_track(0);
+        assert_eq!(*disposed.lock().unwrap(), vec!["idle".to_owned()]);
+        assert!(track_handle.get(&animation_state).is_none());
+    }
+
+    /// A handle must not resolve against a new animation state allocated at the address of the
+    /// dropped one.
+    #[test]
+    fn track_entry_invalidate_drop() {
+        let (_, animation_state_data) = TestAsset::spineboy().instance_data();
+        let mut animation_state = AnimationState::new(animation_state_data.clone());
+        let _ = animation_state.set_animation_by_name(0, "idle", true);
+        let track_handle = animation_state.track_at_index(0).unwrap().handle();
+        let dropped_id = animation_state.handle_id();
+        drop(animation_state);
+
+        let mut animation_state = AnimationState::new(animation_state_data);
+        let _ = animation_state.set_animation_by_name(0, "idle", true);
+        assert!(track_handle.get(&animation_state).is_none());
+
+        // A handle created from the dropped animation state, as if this one was allocated at its
+        // address.
+        let new_track_handle = animation_state.track_at_index(0).unwrap().handle();
+        let dropped_track_handle = TrackEntryHandle::new(
+            0,
+            new_track_handle.c_item.0,
+            animation_state.c_ptr(),
+            dropped_id,
+            new_track_handle.item_id,
+        );
+        assert!(dropped_track_handle.get(&animation_state).is_none());
+        assert!(dropped_track_handle.get_mut(&mut animation_state).is_none());
+        assert!(new_track_handle.get(&animation_state).is_some());
+    }
 }
@@ -5,9 +5,9 @@ use crate::{
     c::{
         c_void, spAnimationStateData, spAnimationStateData_create, spAnimationStateData_dispose,
         spAnimationStateData_getMix, spAnimationStateData_setMix,
-        spAnimationStateData_setMixByName, spSkeletonData,
+        spAnimationStateData_setMixByName,
     },
-    c_interface::{to_c_str, NewFromPtr, SyncPtr},
+    c_interface::{to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     skeleton_data::SkeletonData,
 };
 
@@ -89,13 +89,32 @@ impl AnimationStateData {
         unsafe { spAnimationStateData_getMix(self.c_ptr(), from.c_ptr(), to.c_ptr()) }
     }
 
-    c_accessor_tmp_ptr_mut!(
-        skeleton_data,
-        skeleton_data_mut,
-        skeletonData,
-        SkeletonData,
-        spSkeletonData
-    );
+    #[must_use]
+    pub fn skeleton_data(&self) -> CTmpRef<'_, Self, SkeletonData> {
+        CTmpRef::new(self, unsafe {
+            SkeletonData::new_borrowed(
+                self.c_ptr_ref().skeletonData,
+                self.skeleton_data_handle_id(),
+            )
+        })
+    }
+
+    #[must_use]
+    pub fn skeleton_data_mut(&mut self) -> CTmpMut<'_, Self, SkeletonData> {
+        let c_skeleton_data = unsafe { self.c_ptr_ref().skeletonData };
+        let skeleton_data_handle_id = self.skeleton_data_handle_id();
+        CTmpMut::new(self, unsafe {
+            SkeletonData::new_borrowed(c_skeleton_data, skeleton_data_handle_id)
+        })
+    }
+
+    /// The handle id of the [`SkeletonData`] this was created with, or `0` if not owned.
+    fn skeleton_data_handle_id(&self) -> u64 {
+        self._skeleton_data
+            .as_ref()
+            .map_or(0, |skeleton_data| skeleton_data.handle_id())
+    }
+
     c_accessor_mut!(default_mix, set_default_mix, defaultMix, f32);
     c_accessor_passthrough!(entries, entries, *const c_void);
     c_ptr!(c_animation_state_data, spAnimationStateData);
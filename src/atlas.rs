@@ -1,11 +1,18 @@
+use std::ffi::c_void;
 use std::ffi::CString;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
 use std::{path::Path, ptr::null_mut};
 
+use crate::atlas_validator::validate_atlas;
 use crate::c::{spAtlasFilter, spAtlasFormat, spAtlasRegion, spAtlasWrap, spAtlas_createFromFile};
-use crate::c_interface::{CTmpRef, NewFromPtr, SyncPtr};
+use crate::c_interface::{new_handle_id, CTmpRef, NewFromPtr, SyncPtr};
 use crate::{
     c::{c_int, spAtlas, spAtlasPage, spAtlas_create, spAtlas_dispose},
     error::SpineError,
+    extension::{self, ExtensionCallbacks},
+    fs::SpineFs,
+    memory::{self, MemoryCategory},
 };
 
 use atlas::*;
@@ -14,7 +21,7 @@ use atlas::*;
 use std::ffi::CStr;
 
 #[cfg(not(feature = "spine38"))]
-use crate::{c::spTextureRegion, texture_region::TextureRegion};
+use crate::{c::spTextureRegion, c_interface::from_c_str, texture_region::TextureRegion};
 
 #[cfg(feature = "mint")]
 use mint::Vector2;
@@ -26,6 +33,9 @@ use mint::Vector2;
 pub struct Atlas {
     c_atlas: SyncPtr<spAtlas>,
     owns_memory: bool,
+    handle_id: u64,
+    extension: Option<Arc<ExtensionCallbacks>>,
+    deferred_pages: Vec<PendingPage>,
 }
 
 impl NewFromPtr<spAtlas> for Atlas {
@@ -33,6 +43,9 @@ impl NewFromPtr<spAtlas> for Atlas {
         Atlas {
             c_atlas: SyncPtr(c_atlas),
             owns_memory: false,
+            handle_id: 0,
+            extension: None,
+            deferred_pages: vec![],
         }
     }
 }
@@ -53,27 +66,89 @@ impl Atlas {
     /// Returns the [`SpineError::NulError`] if `dir` or `data` contain an internal 0 byte. Returns
     /// [`SpineError::PathNotUtf8`] if the specified `dir` is not utf-8. This function does not
     /// error if the atlas file is invalid or malformed. The file is parsed line-by-line and invalid
-    /// lines are simply ignored.
+    /// lines are simply ignored, see [`Atlas::new_strict`] or [`Atlas::new_lenient`] to find
+    /// these problems.
     pub fn new<P: AsRef<Path>>(data: &[u8], dir: P) -> Result<Atlas, SpineError> {
         let c_data = CString::new(data)?;
         let Some(dir_path) = dir.as_ref().to_str() else {
             return Err(SpineError::PathNotUtf8);
         };
         let c_dir = CString::new(dir_path)?;
-        let c_atlas = unsafe {
+        let c_atlas = memory::with_category(MemoryCategory::Atlas, || unsafe {
             spAtlas_create(
                 c_data.as_ptr(),
                 data.len() as c_int,
                 c_dir.as_ptr(),
                 null_mut(),
             )
-        };
+        });
         Ok(Self {
             c_atlas: SyncPtr(c_atlas),
             owns_memory: true,
+            handle_id: new_handle_id(),
+            extension: extension::texture_scope(),
+            deferred_pages: vec![],
         })
     }
 
+    /// Create an Atlas from an in-memory vector, using `extension` to create and dispose its
+    /// textures instead of the global callbacks. See [`ExtensionCallbacks`].
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new`].
+    pub fn new_with_extension<P: AsRef<Path>>(
+        data: &[u8],
+        dir: P,
+        extension: Arc<ExtensionCallbacks>,
+    ) -> Result<Atlas, SpineError> {
+        let _scope = extension::scope(extension.clone());
+        let mut atlas = Self::new(data, dir)?;
+        atlas.extension = Some(extension);
+        Ok(atlas)
+    }
+
+    /// Create an Atlas from an in-memory vector, returning an error for the first problem found in
+    /// the atlas file instead of ignoring it like [`Atlas::new`] does.
+    ///
+    /// Problems include unknown keys, values which are not valid numbers or names, regions before
+    /// the first page, and regions with the same name and index as an earlier region. See
+    /// [`AtlasDiagnosticKind`]. With the `spine38` feature, no problems are reported, since the
+    /// 3.8 runtime reads atlas entries in a fixed order and fails to load malformed files.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::InvalidAtlas`] with the line number and reason for the first problem
+    /// found in `data`, or any error from [`Atlas::new`].
+    pub fn new_strict<P: AsRef<Path>>(data: &[u8], dir: P) -> Result<Atlas, SpineError> {
+        if let Some(diagnostic) = Self::validate(data).into_iter().next() {
+            return Err(diagnostic.into());
+        }
+        Self::new(data, dir)
+    }
+
+    /// Create an Atlas from an in-memory vector, returning any problems found in the atlas file
+    /// alongside it. The problems are the same as those reported by [`Atlas::new_strict`], and
+    /// are ignored when loading the atlas.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new`].
+    pub fn new_lenient<P: AsRef<Path>>(
+        data: &[u8],
+        dir: P,
+    ) -> Result<(Atlas, Vec<AtlasDiagnostic>), SpineError> {
+        let diagnostics = Self::validate(data);
+        Ok((Self::new(data, dir)?, diagnostics))
+    }
+
+    /// Check the contents of an atlas file for problems without loading it, see
+    /// [`Atlas::new_strict`].
+    #[must_use]
+    pub fn validate(data: &[u8]) -> Vec<AtlasDiagnostic> {
+        validate_atlas(&String::from_utf8_lossy(data))
+    }
+
     /// Create an Atlas from a file.
     /// ```
     /// use rusty_spine::Atlas;
@@ -93,11 +168,16 @@ impl Atlas {
             return Err(SpineError::PathNotUtf8);
         };
         let c_path = CString::new(path_str)?;
-        let c_atlas = unsafe { spAtlas_createFromFile(c_path.as_ptr(), null_mut()) };
+        let c_atlas = memory::with_category(MemoryCategory::Atlas, || unsafe {
+            spAtlas_createFromFile(c_path.as_ptr(), null_mut())
+        });
         if !c_atlas.is_null() {
             Ok(Self {
                 c_atlas: SyncPtr(c_atlas),
                 owns_memory: true,
+                handle_id: new_handle_id(),
+                extension: extension::texture_scope(),
+                deferred_pages: vec![],
             })
         } else {
             Err(SpineError::FailedToReadFile {
@@ -106,6 +186,137 @@ impl Atlas {
         }
     }
 
+    /// Create an Atlas from a file in `fs`, see [`SpineFs`]. The path of each page image is
+    /// resolved from `path` and the page name with [`SpineFs::resolve`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::FailedToReadFile`] if the file could not be read. Returns
+    /// [`SpineError::PathNotUtf8`] if a resolved page path is not utf-8. Returns any error from
+    /// [`Atlas::new`].
+    pub fn new_from_fs<F: SpineFs + ?Sized, P: AsRef<Path>>(
+        fs: &F,
+        path: P,
+    ) -> Result<Atlas, SpineError> {
+        let path = path.as_ref();
+        let data = fs
+            .read(path)
+            .map_err(|_| SpineError::new_failed_to_read_file(path))?;
+        let mut atlas = Self::new_deferred(&data, fs.resolve(path, Path::new("")))?;
+        for pending_page in std::mem::take(&mut atlas.deferred_pages) {
+            if let Some(mut page) = pending_page.handle.get_mut(&mut atlas) {
+                let page_path = fs.resolve(path, Path::new(page.name()));
+                let Some(page_path) = page_path.to_str() else {
+                    return Err(SpineError::PathNotUtf8);
+                };
+                extension::create_texture(&mut page, page_path);
+            }
+        }
+        Ok(atlas)
+    }
+
+    /// Create an Atlas from a file, using `extension` to read the file and to create and dispose
+    /// its textures instead of the global callbacks. See [`ExtensionCallbacks`].
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new_from_file`].
+    pub fn new_from_file_with_extension<P: AsRef<Path>>(
+        path: P,
+        extension: Arc<ExtensionCallbacks>,
+    ) -> Result<Atlas, SpineError> {
+        let _scope = extension::scope(extension.clone());
+        let mut atlas = Self::new_from_file(path)?;
+        atlas.extension = Some(extension);
+        Ok(atlas)
+    }
+
+    /// Create an Atlas from an in-memory vector without creating its textures, so they can be
+    /// loaded later (for example, on another thread). The texture callbacks are not called;
+    /// instead, each page is listed in [`Atlas::pending_pages`] until its texture is provided with
+    /// [`AtlasPage::set_texture`]. Textures are still disposed by the dispose callback.
+    ///
+    /// ```
+    /// # #[path="./test.rs"]
+    /// # mod test;
+    /// use rusty_spine::Atlas;
+    ///
+    /// struct SpineTexture(pub String);
+    ///
+    /// let atlas = Atlas::new_deferred(test::TestAsset::spineboy().atlas_data, "assets")?;
+    /// let pending_pages = atlas.pending_pages();
+    /// // Decode the textures in the background, then on the main thread:
+    /// for pending_page in pending_pages {
+    ///     let page = pending_page.handle.get(&atlas).unwrap();
+    ///     page.set_texture(SpineTexture(pending_page.path));
+    /// }
+    /// assert!(atlas.pending_pages().is_empty());
+    /// # for page in atlas.pages() {
+    /// #     unsafe { page.renderer_object().dispose::<SpineTexture>() };
+    /// # }
+    /// # Ok::<(), rusty_spine::SpineError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new`].
+    pub fn new_deferred<P: AsRef<Path>>(data: &[u8], dir: P) -> Result<Atlas, SpineError> {
+        Self::create_deferred(|| Self::new(data, dir))
+    }
+
+    /// Create an Atlas from a file without creating its textures, see [`Atlas::new_deferred`].
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new_from_file`].
+    pub fn new_from_file_deferred<P: AsRef<Path>>(path: P) -> Result<Atlas, SpineError> {
+        Self::create_deferred(|| Self::new_from_file(path))
+    }
+
+    fn create_deferred<F>(create: F) -> Result<Atlas, SpineError>
+    where
+        F: FnOnce() -> Result<Atlas, SpineError>,
+    {
+        let pending = Arc::new(Mutex::new(vec![]));
+        let recorded = pending.clone();
+        let deferred = ExtensionCallbacks::new().with_create_texture_cb(move |page, path| {
+            let page = SyncPtr(page.c_ptr());
+            recorded.lock().unwrap().push((page, path.to_owned()));
+        });
+        let extension = extension::texture_scope();
+        let mut atlas = {
+            let _scope = extension::scope(Arc::new(deferred));
+            create()?
+        };
+        atlas.extension = extension;
+        let c_atlas = atlas.c_ptr();
+        atlas.deferred_pages = pending
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|(page, path)| PendingPage {
+                handle: AtlasPageHandle::new(page.0, c_atlas, atlas.handle_id),
+                path,
+            })
+            .collect();
+        Ok(atlas)
+    }
+
+    /// The pages of an atlas created with [`Atlas::new_deferred`] which do not have a texture yet.
+    #[must_use]
+    pub fn pending_pages(&self) -> Vec<PendingPage> {
+        self.deferred_pages
+            .iter()
+            .filter(|pending_page| {
+                pending_page
+                    .handle
+                    .get(self)
+                    .is_some_and(|page| !page.texture_ready())
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Iterator over the [`AtlasPage`] list in this atlas.
     #[must_use]
     pub fn pages(&self) -> AtlasPageIterator {
@@ -137,12 +348,19 @@ impl Atlas {
     }
 
     c_accessor_renderer_object!();
+    /// The id checked by handles to this object, so they are not valid for another object at the
+    /// same address.
+    pub(crate) const fn handle_id(&self) -> u64 {
+        self.handle_id
+    }
+
     c_ptr!(c_atlas, spAtlas);
 }
 
 impl Drop for Atlas {
     fn drop(&mut self) {
         if self.owns_memory {
+            let _scope = self.extension.clone().map(extension::scope);
             unsafe {
                 spAtlas_dispose(self.c_atlas.0);
             }
@@ -157,6 +375,9 @@ pub mod atlas {
 
     use super::*;
 
+    pub use crate::atlas_builder::{AtlasBuilder, AtlasPageDesc, AtlasRegionDesc};
+    pub use crate::atlas_validator::{AtlasDiagnostic, AtlasDiagnosticKind};
+
     /// Settings for an atlas backing texture contained in [`Atlas`].
     ///
     /// [Spine API Reference](http://esotericsoftware.com/spine-api-reference#AtlasPage)
@@ -235,9 +456,84 @@ pub mod atlas {
             pma
         );
         c_accessor_renderer_object!();
+
+        /// Returns true if this page has a texture, which is set by the create texture callback or
+        /// by [`AtlasPage::set_texture`].
+        #[must_use]
+        pub fn texture_ready(&self) -> bool {
+            !self
+                .atomic_renderer_object()
+                .load(Ordering::Acquire)
+                .is_null()
+        }
+
+        /// Provide the texture for a page of an atlas created with
+        /// [`Atlas::new_deferred`](`crate::Atlas::new_deferred`), storing it on the renderer object
+        /// like the create texture callback would.
+        ///
+        /// The renderer object is written atomically, so this can be called while the atlas is
+        /// shared with skeletons being drawn on other threads, as long as they check
+        /// [`AtlasPage::texture_ready`] before reading it.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the page already has a texture, or if given a zero sized type.
+        pub fn set_texture<T>(&self, texture: T) {
+            assert!(
+                std::mem::size_of::<T>() != 0,
+                "Renderer object cannot be set to an empty type, please add member variables: {}",
+                std::any::type_name::<T>()
+            );
+            let ptr = Box::into_raw(Box::new(texture));
+            if self
+                .atomic_renderer_object()
+                .compare_exchange(null_mut(), ptr.cast(), Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+            {
+                drop(unsafe { Box::from_raw(ptr) });
+                panic!("Setting renderer object when it's already set.");
+            }
+        }
+
+        fn atomic_renderer_object(&self) -> &AtomicPtr<c_void> {
+            // `AtomicPtr` has the same in-memory representation as a raw pointer.
+            unsafe {
+                &*std::ptr::addr_of_mut!((*self.c_ptr()).rendererObject).cast::<AtomicPtr<c_void>>()
+            }
+        }
+
         c_ptr!(c_atlas_page, spAtlasPage);
     }
 
+    c_handle_decl!(
+        /// A storeable reference to an [`AtlasPage`].
+        ///
+        /// Can be acquired from a [`CTmpRef<Atlas, AtlasPage>`] or from
+        /// [`Atlas::pending_pages`](`crate::Atlas::pending_pages`).
+        AtlasPageHandle,
+        AtlasPage,
+        Atlas,
+        spAtlasPage,
+        spAtlas
+    );
+
+    impl<'a> CTmpRef<'a, Atlas, AtlasPage> {
+        #[must_use]
+        pub fn handle(&self) -> AtlasPageHandle {
+            AtlasPageHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
+        }
+    }
+
+    /// A page of an atlas created with [`Atlas::new_deferred`](`crate::Atlas::new_deferred`)
+    /// which is waiting for its texture.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PendingPage {
+        pub handle: AtlasPageHandle,
+        /// The path of the image file, as it would have been passed to the create texture
+        /// callback.
+        pub path: String,
+    }
+
     /// Functions available if using the `mint` feature.
     #[cfg(feature = "mint")]
     impl AtlasPage {
@@ -273,6 +569,7 @@ pub mod atlas {
 
     /// The texture format setting to use for an [`AtlasPage`].
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum AtlasFormat {
         UnknownFormat = 0,
         Alpha = 1,
@@ -301,6 +598,7 @@ pub mod atlas {
 
     /// The texture filter setting to use for an [`AtlasPage`].
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum AtlasFilter {
         UnknownFilter = 0,
         Nearest = 1,
@@ -329,6 +627,7 @@ pub mod atlas {
 
     /// The texture wrapping setting to use for an [`AtlasPage`].
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum AtlasWrap {
         MirroredRepeat = 0,
         ClampToEdge = 1,
@@ -399,7 +698,8 @@ pub mod atlas {
     }
 
     /// Additional key-value pairs in an [`AtlasRegion`].
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct KeyValue {
         pub name: String,
         pub values: [f32; 5],
@@ -440,10 +740,35 @@ pub mod atlas {
 
 #[cfg(test)]
 mod tests {
-    use crate::test::TestAsset;
+    use crate::{error::SpineError, test::TestAsset};
 
     use super::Atlas;
 
+    #[test]
+    fn new_deferred() {
+        let asset = TestAsset::dragon();
+        let atlas = Atlas::new_from_file_deferred(asset.atlas_file).unwrap();
+        let pending_pages = atlas.pending_pages();
+        assert_eq!(pending_pages.len(), atlas.pages().count());
+        for (pending_page, page) in pending_pages.iter().zip(atlas.pages()) {
+            assert_eq!(pending_page.handle, page.handle());
+            assert_eq!(
+                pending_page.path,
+                format!("assets/dragon/export/{}", page.name())
+            );
+            assert!(!page.texture_ready());
+        }
+        let page = pending_pages[1].handle.get(&atlas).unwrap();
+        page.set_texture(pending_pages[1].path.clone());
+        assert!(page.texture_ready());
+        assert_eq!(atlas.pending_pages().len(), pending_pages.len() - 1);
+        unsafe { page.renderer_object().dispose::<String>() };
+
+        let other = Atlas::new_from_file(asset.atlas_file).unwrap();
+        assert!(pending_pages[0].handle.get(&other).is_none());
+        assert!(other.pending_pages().is_empty());
+    }
+
     #[test]
     fn new_from_file() {
         for test_example_asset in TestAsset::all() {
@@ -454,4 +779,19 @@ mod tests {
         let atlas = Atlas::new_from_file(format!("missing/{}", TestAsset::spineboy().atlas_file));
         assert!(atlas.is_err());
     }
+
+    #[test]
+    fn new_strict() {
+        let data = std::fs::read(TestAsset::spineboy().atlas_file).unwrap();
+        assert!(Atlas::new_strict(&data, "").is_ok());
+
+        let data = b"sheet.png\n\tsize: 64, 64\nhead\n\tbounds: 0, 0, 32, 3.5\n";
+        assert!(matches!(
+            Atlas::new_strict(data, ""),
+            Err(SpineError::InvalidAtlas { line: 4, .. })
+        ));
+        let (atlas, diagnostics) = Atlas::new_lenient(data, "").unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(atlas.find_region("head").is_some());
+    }
 }
@@ -0,0 +1,517 @@
+use std::{fmt, path::Path};
+
+use crate::{
+    atlas::{AtlasFilter, AtlasFormat, AtlasWrap},
+    error::SpineError,
+    Atlas,
+};
+
+/// Programmatically construct an atlas, either to write it to the `.atlas` file format or to load
+/// it directly as an [`Atlas`].
+///
+/// ```
+/// use rusty_spine::atlas::{AtlasBuilder, AtlasPageDesc, AtlasRegionDesc};
+///
+/// let builder = AtlasBuilder::new().with_page(
+///     AtlasPageDesc::new("sheet.png", 256, 256)
+///         .with_region(AtlasRegionDesc::new("head", 0, 0, 64, 64))
+///         .with_region(AtlasRegionDesc::new("eye", 64, 0, 16, 16).with_index(0)),
+/// );
+/// let path = std::env::temp_dir().join("sheet.atlas");
+/// std::fs::write(&path, builder.to_string()).unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// let atlas = builder.build("").unwrap();
+/// assert_eq!(atlas.regions().count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasBuilder {
+    pub pages: Vec<AtlasPageDesc>,
+}
+
+impl AtlasBuilder {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { pages: vec![] }
+    }
+
+    #[must_use]
+    pub fn with_page(mut self, page: AtlasPageDesc) -> Self {
+        self.pages.push(page);
+        self
+    }
+
+    /// Create an [`AtlasBuilder`] describing the pages and regions of an existing atlas.
+    ///
+    /// Key-value pairs are read from the runtime, which does not keep track of how many values
+    /// each had, so trailing zeros are removed.
+    #[cfg(not(feature = "spine38"))]
+    #[must_use]
+    pub fn from_atlas(atlas: &Atlas) -> Self {
+        let mut builder = Self::new();
+        for page in atlas.pages() {
+            let mut page_desc = AtlasPageDesc::new(page.name(), page.width(), page.height())
+                .with_format(page.format())
+                .with_filter(page.min_filter(), page.mag_filter())
+                .with_wrap(page.u_wrap(), page.v_wrap())
+                .with_pma(page.pma());
+            for region in atlas.regions() {
+                if region.page().c_ptr() != page.c_ptr() {
+                    continue;
+                }
+                let texture_region = region.texture_region();
+                let mut region_desc = AtlasRegionDesc::new(
+                    region.name(),
+                    region.x(),
+                    region.y(),
+                    texture_region.width(),
+                    texture_region.height(),
+                )
+                .with_offsets(
+                    texture_region.offset_x() as i32,
+                    texture_region.offset_y() as i32,
+                    texture_region.original_width(),
+                    texture_region.original_height(),
+                )
+                .with_degrees(texture_region.degrees())
+                .with_index(unsafe { region.c_ptr_ref().index });
+                for key_value in region.key_values() {
+                    let count = key_value
+                        .values
+                        .iter()
+                        .rposition(|value| *value != 0.)
+                        .map_or(1, |index| index + 1);
+                    let values: Vec<i32> = key_value.values[..count]
+                        .iter()
+                        .map(|value| *value as i32)
+                        .collect();
+                    region_desc = region_desc.with_key_value(&key_value.name, &values);
+                }
+                page_desc = page_desc.with_region(region_desc);
+            }
+            builder = builder.with_page(page_desc);
+        }
+        builder
+    }
+
+    /// Load the described pages and regions as an [`Atlas`], with page images relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`Atlas::new`].
+    pub fn build<P: AsRef<Path>>(&self, dir: P) -> Result<Atlas, SpineError> {
+        Atlas::new(self.to_string().as_bytes(), dir)
+    }
+}
+
+/// Writes the libGDX/Spine `.atlas` text format, in the layout expected by the Spine version this
+/// crate was built for.
+impl fmt::Display for AtlasBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, page) in self.pages.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            page.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A page in an [`AtlasBuilder`], see [`AtlasPage`](`crate::atlas::AtlasPage`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasPageDesc {
+    /// The name of the image file for the texture.
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub format: AtlasFormat,
+    pub min_filter: AtlasFilter,
+    pub mag_filter: AtlasFilter,
+    /// Only [`AtlasWrap::Repeat`] can be stored in atlas files, any other setting is loaded as
+    /// [`AtlasWrap::ClampToEdge`].
+    pub u_wrap: AtlasWrap,
+    /// See [`AtlasPageDesc::u_wrap`].
+    pub v_wrap: AtlasWrap,
+    /// The premultiplied alpha setting. Not supported by Spine 3.8 atlas files.
+    pub pma: bool,
+    pub regions: Vec<AtlasRegionDesc>,
+}
+
+impl AtlasPageDesc {
+    /// Create an RGBA8888 page with linear filtering and no texture repeat.
+    #[must_use]
+    pub fn new(name: &str, width: i32, height: i32) -> Self {
+        Self {
+            name: name.to_owned(),
+            width,
+            height,
+            format: AtlasFormat::RGBA8888,
+            min_filter: AtlasFilter::Linear,
+            mag_filter: AtlasFilter::Linear,
+            u_wrap: AtlasWrap::ClampToEdge,
+            v_wrap: AtlasWrap::ClampToEdge,
+            pma: false,
+            regions: vec![],
+        }
+    }
+
+    #[must_use]
+    pub const fn with_format(mut self, format: AtlasFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_filter(mut self, min_filter: AtlasFilter, mag_filter: AtlasFilter) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_wrap(mut self, u_wrap: AtlasWrap, v_wrap: AtlasWrap) -> Self {
+        self.u_wrap = u_wrap;
+        self.v_wrap = v_wrap;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_pma(mut self, pma: bool) -> Self {
+        self.pma = pma;
+        self
+    }
+
+    #[must_use]
+    pub fn with_region(mut self, region: AtlasRegionDesc) -> Self {
+        self.regions.push(region);
+        self
+    }
+
+    const fn repeat(&self) -> &'static str {
+        match (
+            matches!(self.u_wrap, AtlasWrap::Repeat),
+            matches!(self.v_wrap, AtlasWrap::Repeat),
+        ) {
+            (true, true) => "xy",
+            (true, false) => "x",
+            (false, true) => "y",
+            (false, false) => "none",
+        }
+    }
+}
+
+impl fmt::Display for AtlasPageDesc {
+    #[cfg(not(feature = "spine38"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        writeln!(f, "\tsize: {}, {}", self.width, self.height)?;
+        if self.format != AtlasFormat::UnknownFormat {
+            writeln!(f, "\tformat: {}", format_name(self.format))?;
+        }
+        if self.min_filter != AtlasFilter::UnknownFilter
+            && self.mag_filter != AtlasFilter::UnknownFilter
+        {
+            writeln!(
+                f,
+                "\tfilter: {}, {}",
+                filter_name(self.min_filter),
+                filter_name(self.mag_filter)
+            )?;
+        }
+        if self.repeat() != "none" {
+            writeln!(f, "\trepeat: {}", self.repeat())?;
+        }
+        if self.pma {
+            writeln!(f, "\tpma: true")?;
+        }
+        for region in &self.regions {
+            region.fmt(f)?;
+        }
+        Ok(())
+    }
+
+    // Spine 3.8 reads every page and region entry in a fixed order, regardless of its key.
+    #[cfg(feature = "spine38")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        writeln!(f, "size: {},{}", self.width, self.height)?;
+        writeln!(f, "format: {}", format_name(self.format))?;
+        writeln!(
+            f,
+            "filter: {},{}",
+            filter_name(self.min_filter),
+            filter_name(self.mag_filter)
+        )?;
+        writeln!(f, "repeat: {}", self.repeat())?;
+        for region in &self.regions {
+            region.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A region in an [`AtlasPageDesc`], see [`AtlasRegion`](`crate::atlas::AtlasRegion`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasRegionDesc {
+    pub name: String,
+    /// The left edge of the region on the page, in pixels.
+    pub x: i32,
+    /// The top edge of the region on the page, in pixels.
+    pub y: i32,
+    /// The width of the region before rotation, with whitespace stripped.
+    pub width: i32,
+    /// The height of the region before rotation, with whitespace stripped.
+    pub height: i32,
+    /// The number of pixels stripped from the left of the original image.
+    pub offset_x: i32,
+    /// The number of pixels stripped from the bottom of the original image.
+    pub offset_y: i32,
+    /// The width of the original image, before whitespace was stripped.
+    pub original_width: i32,
+    /// The height of the original image, before whitespace was stripped.
+    pub original_height: i32,
+    /// The rotation of the region on the page, counter clockwise. Spine 3.8 only supports 0 or 90.
+    pub degrees: i32,
+    /// The index of this image in a sequence, or -1 if it is not part of a sequence.
+    pub index: i32,
+    /// Nine-patch splits (left, right, top, bottom), written as the `split` key.
+    pub splits: Option<[i32; 4]>,
+    /// Nine-patch padding (left, right, top, bottom), written as the `pad` key.
+    pub pads: Option<[i32; 4]>,
+    /// Additional key-value pairs with up to 4 values each, not supported by Spine 3.8 atlas
+    /// files.
+    pub key_values: Vec<(String, Vec<i32>)>,
+}
+
+impl AtlasRegionDesc {
+    /// Create an untrimmed, unrotated region.
+    #[must_use]
+    pub fn new(name: &str, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            name: name.to_owned(),
+            x,
+            y,
+            width,
+            height,
+            offset_x: 0,
+            offset_y: 0,
+            original_width: width,
+            original_height: height,
+            degrees: 0,
+            index: -1,
+            splits: None,
+            pads: None,
+            key_values: vec![],
+        }
+    }
+
+    /// Set the whitespace stripped from the original image.
+    #[must_use]
+    pub const fn with_offsets(
+        mut self,
+        offset_x: i32,
+        offset_y: i32,
+        original_width: i32,
+        original_height: i32,
+    ) -> Self {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self.original_width = original_width;
+        self.original_height = original_height;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_degrees(mut self, degrees: i32) -> Self {
+        self.degrees = degrees;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_index(mut self, index: i32) -> Self {
+        self.index = index;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_splits(mut self, splits: [i32; 4]) -> Self {
+        self.splits = Some(splits);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_pads(mut self, pads: [i32; 4]) -> Self {
+        self.pads = Some(pads);
+        self
+    }
+
+    /// Add a key-value pair. The keys `split` and `pad` set [`AtlasRegionDesc::splits`] and
+    /// [`AtlasRegionDesc::pads`] instead.
+    #[must_use]
+    pub fn with_key_value(mut self, name: &str, values: &[i32]) -> Self {
+        match (name, values) {
+            ("split", &[left, right, top, bottom]) => {
+                self.splits = Some([left, right, top, bottom]);
+            }
+            ("pad", &[left, right, top, bottom]) => self.pads = Some([left, right, top, bottom]),
+            _ => self.key_values.push((name.to_owned(), values.to_vec())),
+        }
+        self
+    }
+}
+
+impl fmt::Display for AtlasRegionDesc {
+    #[cfg(not(feature = "spine38"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        writeln!(
+            f,
+            "\tbounds: {}, {}, {}, {}",
+            self.x, self.y, self.width, self.height
+        )?;
+        if self.offset_x != 0
+            || self.offset_y != 0
+            || self.original_width != self.width
+            || self.original_height != self.height
+        {
+            writeln!(
+                f,
+                "\toffsets: {}, {}, {}, {}",
+                self.offset_x, self.offset_y, self.original_width, self.original_height
+            )?;
+        }
+        if self.degrees != 0 {
+            writeln!(f, "\trotate: {}", self.degrees)?;
+        }
+        if self.index != -1 {
+            writeln!(f, "\tindex: {}", self.index)?;
+        }
+        let splits = self.splits.map(|splits| ("split", splits.to_vec()));
+        let pads = self.pads.map(|pads| ("pad", pads.to_vec()));
+        let key_values = self
+            .key_values
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.clone()));
+        for (name, values) in splits.into_iter().chain(pads).chain(key_values) {
+            let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+            writeln!(f, "\t{}: {}", name, values.join(", "))?;
+        }
+        Ok(())
+    }
+
+    // Spine 3.8 reads every page and region entry in a fixed order, regardless of its key.
+    #[cfg(feature = "spine38")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        match self.degrees {
+            0 => writeln!(f, "  rotate: false")?,
+            90 => writeln!(f, "  rotate: true")?,
+            degrees => writeln!(f, "  rotate: {degrees}")?,
+        }
+        writeln!(f, "  xy: {}, {}", self.x, self.y)?;
+        writeln!(f, "  size: {}, {}", self.width, self.height)?;
+        // Pads are only read after splits.
+        if self.splits.is_some() || self.pads.is_some() {
+            let [left, right, top, bottom] = self.splits.unwrap_or_default();
+            writeln!(f, "  split: {left}, {right}, {top}, {bottom}")?;
+        }
+        if let Some([left, right, top, bottom]) = self.pads {
+            writeln!(f, "  pad: {left}, {right}, {top}, {bottom}")?;
+        }
+        writeln!(
+            f,
+            "  orig: {}, {}",
+            self.original_width, self.original_height
+        )?;
+        writeln!(f, "  offset: {}, {}", self.offset_x, self.offset_y)?;
+        writeln!(f, "  index: {}", self.index)
+    }
+}
+
+const fn format_name(format: AtlasFormat) -> &'static str {
+    match format {
+        AtlasFormat::UnknownFormat => "",
+        AtlasFormat::Alpha => "Alpha",
+        AtlasFormat::Intensity => "Intensity",
+        AtlasFormat::LuminanceAlpha => "LuminanceAlpha",
+        AtlasFormat::RGB565 => "RGB565",
+        AtlasFormat::RGBA4444 => "RGBA4444",
+        AtlasFormat::RGB888 => "RGB888",
+        AtlasFormat::RGBA8888 => "RGBA8888",
+    }
+}
+
+const fn filter_name(filter: AtlasFilter) -> &'static str {
+    match filter {
+        AtlasFilter::UnknownFilter => "",
+        AtlasFilter::Nearest => "Nearest",
+        AtlasFilter::Linear => "Linear",
+        AtlasFilter::Mipmap => "MipMap",
+        AtlasFilter::MipmapNearestNearest => "MipMapNearestNearest",
+        AtlasFilter::MipmapLinearNearest => "MipMapLinearNearest",
+        AtlasFilter::MipmapNearestLinear => "MipMapNearestLinear",
+        AtlasFilter::MipmapLinearLinear => "MipMapLinearLinear",
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "spine38"))]
+mod tests {
+    use crate::{test::TestAsset, Atlas};
+
+    use super::*;
+
+    /// Rebuilding each test atlas should produce a valid atlas file with identical regions.
+    #[test]
+    fn round_trip() {
+        for asset in TestAsset::all() {
+            let data = std::fs::read(asset.atlas_file).unwrap();
+            let atlas = Atlas::new(&data, "").unwrap();
+            let builder = AtlasBuilder::from_atlas(&atlas);
+            let text = builder.to_string();
+            assert_eq!(
+                Atlas::validate(text.as_bytes()),
+                vec![],
+                "{}",
+                asset.atlas_file
+            );
+            let rebuilt = builder.build("").unwrap();
+            assert_eq!(AtlasBuilder::from_atlas(&rebuilt), builder);
+            assert_eq!(rebuilt.regions().count(), atlas.regions().count());
+        }
+    }
+
+    #[test]
+    fn write_region() {
+        let builder = AtlasBuilder::new().with_page(
+            AtlasPageDesc::new("sheet.png", 128, 64)
+                .with_wrap(AtlasWrap::Repeat, AtlasWrap::ClampToEdge)
+                .with_pma(true)
+                .with_region(
+                    AtlasRegionDesc::new("eye", 2, 4, 10, 20)
+                        .with_offsets(1, 2, 14, 24)
+                        .with_degrees(90)
+                        .with_index(3)
+                        .with_splits([1, 2, 3, 4])
+                        .with_key_value("custom", &[5, 6]),
+                ),
+        );
+        assert_eq!(
+            builder.to_string(),
+            "sheet.png\n\tsize: 128, 64\n\tformat: RGBA8888\n\tfilter: Linear, Linear\n\trepeat: x\n\tpma: true\neye\n\tbounds: 2, 4, 10, 20\n\toffsets: 1, 2, 14, 24\n\trotate: 90\n\tindex: 3\n\tsplit: 1, 2, 3, 4\n\tcustom: 5, 6\n"
+        );
+        let atlas = builder.build("").unwrap();
+        let page = atlas.pages().next().unwrap();
+        assert_eq!(page.u_wrap(), AtlasWrap::Repeat);
+        assert_eq!(page.v_wrap(), AtlasWrap::ClampToEdge);
+        assert!(page.pma());
+        let region = atlas.find_region("eye").unwrap();
+        assert_eq!(region.texture_region().degrees(), 90);
+        assert_eq!(region.texture_region().original_width(), 14);
+        assert_eq!(region.splits(), None);
+        assert_eq!(region.key_values().len(), 2);
+    }
+}
@@ -0,0 +1,311 @@
+use std::{collections::HashMap, fmt};
+
+use crate::error::SpineError;
+
+/// A problem found in an atlas file, see [`Atlas::new_lenient`](`crate::Atlas::new_lenient`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasDiagnostic {
+    /// The line of the atlas file the problem was found on, starting at 1.
+    pub line: usize,
+    pub kind: AtlasDiagnosticKind,
+}
+
+/// The kinds of problems found in atlas files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AtlasDiagnosticKind {
+    /// An entry with a key that the runtime does not recognize, which is ignored when loading.
+    UnknownKey { key: String },
+    /// An entry whose values could not be parsed, or had the wrong number of values.
+    InvalidValue { key: String, value: String },
+    /// A region which appears before any page, which the runtime would mistake for a page.
+    RegionWithoutPage { region: String },
+    /// A region with the same name and index as an earlier region.
+    DuplicateRegion { name: String, first_line: usize },
+}
+
+impl fmt::Display for AtlasDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey { key } => write!(f, "unknown key \"{key}\""),
+            Self::InvalidValue { key, value } => {
+                write!(f, "invalid value for \"{key}\": \"{value}\"")
+            }
+            Self::RegionWithoutPage { region } => {
+                write!(f, "region \"{region}\" is not part of a page")
+            }
+            Self::DuplicateRegion { name, first_line } => {
+                write!(
+                    f,
+                    "duplicate region \"{name}\", first defined on line {first_line}"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for AtlasDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl From<AtlasDiagnostic> for SpineError {
+    fn from(diagnostic: AtlasDiagnostic) -> Self {
+        Self::InvalidAtlas {
+            line: diagnostic.line,
+            reason: diagnostic.kind.to_string(),
+        }
+    }
+}
+
+const FORMATS: [&str; 7] = [
+    "Alpha",
+    "Intensity",
+    "LuminanceAlpha",
+    "RGB565",
+    "RGBA4444",
+    "RGB888",
+    "RGBA8888",
+];
+const FILTERS: [&str; 7] = [
+    "Nearest",
+    "Linear",
+    "MipMap",
+    "MipMapNearestNearest",
+    "MipMapLinearNearest",
+    "MipMapNearestLinear",
+    "MipMapLinearLinear",
+];
+const REPEATS: [&str; 4] = ["x", "y", "xy", "none"];
+const REGION_KEYS: [&str; 10] = [
+    "xy", "size", "bounds", "offset", "orig", "offsets", "rotate", "index", "split", "pad",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Header,
+    None,
+    Page,
+    Region,
+}
+
+struct Region<'a> {
+    name: &'a str,
+    line: usize,
+    index: i32,
+}
+
+/// Check an atlas file for problems that the runtime would silently ignore, following the same
+/// line-based rules the runtime uses to parse it.
+///
+/// The 3.8 runtime reads entries in a fixed order rather than by key, and fails to load files it
+/// cannot parse instead of ignoring lines, so there is nothing to report for it.
+pub(crate) fn validate_atlas<'a>(text: &'a str) -> Vec<AtlasDiagnostic> {
+    if cfg!(feature = "spine38") {
+        return vec![];
+    }
+    let mut diagnostics = vec![];
+    let mut section = Section::Header;
+    let mut page_name = ("", 0);
+    let mut region: Option<Region> = None;
+    let mut seen_regions: HashMap<(&str, i32), usize> = HashMap::new();
+    let mut finish_region = |region: Option<Region<'a>>, diagnostics: &mut Vec<AtlasDiagnostic>| {
+        if let Some(region) = region {
+            if let Some(first_line) = seen_regions.get(&(region.name, region.index)) {
+                diagnostics.push(AtlasDiagnostic {
+                    line: region.line,
+                    kind: AtlasDiagnosticKind::DuplicateRegion {
+                        name: region.name.to_owned(),
+                        first_line: *first_line,
+                    },
+                });
+            } else {
+                seen_regions.insert((region.name, region.index), region.line);
+            }
+        }
+    };
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if section != Section::Header {
+                finish_region(region.take(), &mut diagnostics);
+                section = Section::None;
+            }
+            continue;
+        }
+        let kind = match (section, read_entry(trimmed)) {
+            // Header entries are ignored by the runtime.
+            (Section::Header, Some(_)) => None,
+            (Section::Header | Section::None, None) => {
+                section = Section::Page;
+                page_name = (trimmed, line_number);
+                None
+            }
+            (Section::None, Some((key, _))) => Some(AtlasDiagnosticKind::UnknownKey {
+                key: key.to_owned(),
+            }),
+            (Section::Page, Some((key, values))) => {
+                if REGION_KEYS.contains(&key) && key != "size" {
+                    // The runtime read a region name as a page name. Only report it once.
+                    if page_name.1 != 0 {
+                        diagnostics.push(AtlasDiagnostic {
+                            line: page_name.1,
+                            kind: AtlasDiagnosticKind::RegionWithoutPage {
+                                region: page_name.0.to_owned(),
+                            },
+                        });
+                        page_name.1 = 0;
+                    }
+                    None
+                } else {
+                    validate_page_entry(key, &values)
+                }
+            }
+            (Section::Page | Section::Region, None) => {
+                finish_region(region.take(), &mut diagnostics);
+                section = Section::Region;
+                region = Some(Region {
+                    name: trimmed,
+                    line: line_number,
+                    index: -1,
+                });
+                None
+            }
+            (Section::Region, Some((key, values))) => {
+                let kind = validate_region_entry(key, &values);
+                if let (None, "index", Some(region)) = (&kind, key, &mut region) {
+                    region.index = values[0].parse().unwrap_or(-1);
+                }
+                kind
+            }
+        };
+        if let Some(kind) = kind {
+            diagnostics.push(AtlasDiagnostic {
+                line: line_number,
+                kind,
+            });
+        }
+    }
+    finish_region(region.take(), &mut diagnostics);
+    diagnostics
+}
+
+/// Split a line into a key and its comma separated values, or [`None`] if it is not an entry.
+fn read_entry(line: &str) -> Option<(&str, Vec<&str>)> {
+    let (key, values) = line.split_once(':')?;
+    Some((key.trim(), values.split(',').map(str::trim).collect()))
+}
+
+fn invalid_value(key: &str, values: &[&str]) -> Option<AtlasDiagnosticKind> {
+    Some(AtlasDiagnosticKind::InvalidValue {
+        key: key.to_owned(),
+        value: values.join(", "),
+    })
+}
+
+fn check_ints(key: &str, values: &[&str], count: usize) -> Option<AtlasDiagnosticKind> {
+    if values.len() != count || values.iter().any(|value| value.parse::<i32>().is_err()) {
+        invalid_value(key, values)
+    } else {
+        None
+    }
+}
+
+fn check_names(key: &str, values: &[&str], names: &[&str]) -> Option<AtlasDiagnosticKind> {
+    if values.iter().any(|value| !names.contains(value)) {
+        invalid_value(key, values)
+    } else {
+        None
+    }
+}
+
+fn validate_page_entry(key: &str, values: &[&str]) -> Option<AtlasDiagnosticKind> {
+    match key {
+        "size" => check_ints(key, values, 2),
+        "format" if values.len() == 1 => check_names(key, values, &FORMATS),
+        "filter" if values.len() == 2 => check_names(key, values, &FILTERS),
+        "repeat" if values.len() == 1 => check_names(key, values, &REPEATS),
+        "pma" if values.len() == 1 => check_names(key, values, &["true", "false"]),
+        // Written by the texture packer, but not used by the runtime.
+        "scale" if values.len() == 1 && values[0].parse::<f32>().is_ok() => None,
+        "scale" => invalid_value(key, values),
+        "format" | "filter" | "repeat" | "pma" => invalid_value(key, values),
+        _ => Some(AtlasDiagnosticKind::UnknownKey {
+            key: key.to_owned(),
+        }),
+    }
+}
+
+fn validate_region_entry(key: &str, values: &[&str]) -> Option<AtlasDiagnosticKind> {
+    match key {
+        "xy" | "size" | "offset" | "orig" => check_ints(key, values, 2),
+        "bounds" | "offsets" | "split" | "pad" => check_ints(key, values, 4),
+        "index" => check_ints(key, values, 1),
+        "rotate" if values.len() == 1 && matches!(values[0], "true" | "false") => None,
+        "rotate" => check_ints(key, values, 1),
+        _ => Some(AtlasDiagnosticKind::UnknownKey {
+            key: key.to_owned(),
+        }),
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "spine38"))]
+mod tests {
+    use crate::test::TestAsset;
+
+    use super::*;
+
+    #[test]
+    fn validate_test_assets() {
+        for asset in TestAsset::all() {
+            let text = std::fs::read_to_string(asset.atlas_file).unwrap();
+            assert_eq!(validate_atlas(&text), vec![], "{}", asset.atlas_file);
+        }
+    }
+
+    #[test]
+    fn validate_problems() {
+        let text = "head\n\tbounds: 1, 2, 3, 4\n\nsheet.png\n\tsize: 64, 64\n\tcolor: red\n\tfilter: Linear, Blurry\neye\n\tbounds: 1, 2, x, 4\n\tindex: 1\neye\n\tindex: 2\neye\n\tindex: 1\n";
+        let diagnostics = validate_atlas(text);
+        assert_eq!(
+            diagnostics,
+            vec![
+                AtlasDiagnostic {
+                    line: 1,
+                    kind: AtlasDiagnosticKind::RegionWithoutPage {
+                        region: "head".to_owned()
+                    }
+                },
+                AtlasDiagnostic {
+                    line: 6,
+                    kind: AtlasDiagnosticKind::UnknownKey {
+                        key: "color".to_owned()
+                    }
+                },
+                AtlasDiagnostic {
+                    line: 7,
+                    kind: AtlasDiagnosticKind::InvalidValue {
+                        key: "filter".to_owned(),
+                        value: "Linear, Blurry".to_owned()
+                    }
+                },
+                AtlasDiagnostic {
+                    line: 9,
+                    kind: AtlasDiagnosticKind::InvalidValue {
+                        key: "bounds".to_owned(),
+                        value: "1, 2, x, 4".to_owned()
+                    }
+                },
+                AtlasDiagnostic {
+                    line: 13,
+                    kind: AtlasDiagnosticKind::DuplicateRegion {
+                        name: "eye".to_owned(),
+                        first_line: 8
+                    }
+                },
+            ]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
         spAttachment, spAttachmentType, spAttachment_dispose, spBoundingBoxAttachment,
         spClippingAttachment, spMeshAttachment, spPointAttachment, spRegionAttachment,
     },
-    c_interface::{NewFromPtr, SyncPtr},
+    c_interface::{CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     clipping_attachment::ClippingAttachment,
     mesh_attachment::MeshAttachment,
     point_attachment::PointAttachment,
@@ -34,68 +34,97 @@ impl NewFromPtr<spAttachment> for Attachment {
     }
 }
 
-impl Attachment {
-    /// Get this attachment as a [`RegionAttachment`], or [`None`] if it's a different type.
-    #[must_use]
-    pub fn as_region(&self) -> Option<RegionAttachment> {
-        if self.attachment_type() == AttachmentType::Region {
-            Some(RegionAttachment::new_from_ptr(
-                self.c_attachment.0.cast::<spRegionAttachment>(),
-            ))
-        } else {
-            None
+macro_rules! attachment_cast {
+    ($(#[$($attrss1:tt)*])* $fn:ident, $(#[$($attrss2:tt)*])* $fn_mut:ident, $type:ident, $c_type:ident, $attachment_type:expr) => {
+        $(#[$($attrss1)*])*
+        #[must_use]
+        pub fn $fn(&self) -> Option<CTmpRef<'_, Self, $type>> {
+            if self.attachment_type() == $attachment_type {
+                Some(CTmpRef::new(self, unsafe {
+                    $type::new_from_ptr(self.c_attachment.0.cast::<$c_type>())
+                }))
+            } else {
+                None
+            }
         }
-    }
-
-    /// Get this attachment as a [`BoundingBoxAttachment`], or [`None`] if it's a different type.
-    #[must_use]
-    pub fn as_bounding_box(&self) -> Option<BoundingBoxAttachment> {
-        if self.attachment_type() == AttachmentType::BoundingBox {
-            Some(unsafe {
-                BoundingBoxAttachment::new_from_ptr(
-                    self.c_attachment.0.cast::<spBoundingBoxAttachment>(),
-                )
-            })
-        } else {
-            None
-        }
-    }
 
-    /// Get this attachment as a [`MeshAttachment`], or [`None`] if it's a different type.
-    #[must_use]
-    pub fn as_mesh(&self) -> Option<MeshAttachment> {
-        if self.attachment_type() == AttachmentType::Mesh {
-            Some(unsafe {
-                MeshAttachment::new_from_ptr(self.c_attachment.0.cast::<spMeshAttachment>())
-            })
-        } else {
-            None
+        $(#[$($attrss2)*])*
+        #[must_use]
+        pub fn $fn_mut(&mut self) -> Option<CTmpMut<'_, Self, $type>> {
+            if self.attachment_type() == $attachment_type {
+                let c_attachment = self.c_attachment.0.cast::<$c_type>();
+                Some(CTmpMut::new(self, unsafe { $type::new_from_ptr(c_attachment) }))
+            } else {
+                None
+            }
         }
-    }
-
-    /// Get this attachment as a [`PointAttachment`], or [`None`] if it's a different type.
-    #[must_use]
-    pub fn as_point(&self) -> Option<PointAttachment> {
-        if self.attachment_type() == AttachmentType::Point {
-            Some(unsafe {
-                PointAttachment::new_from_ptr(self.c_attachment.0.cast::<spPointAttachment>())
-            })
-        } else {
-            None
-        }
-    }
+    };
+}
 
-    /// Get this attachment as a [`ClippingAttachment`], or [`None`] if it's a different type.
-    #[must_use]
-    pub fn as_clipping(&self) -> Option<ClippingAttachment> {
-        if self.attachment_type() == AttachmentType::Clipping {
-            Some(unsafe {
-                ClippingAttachment::new_from_ptr(self.c_attachment.0.cast::<spClippingAttachment>())
-            })
-        } else {
-            None
-        }
-    }
+impl Attachment {
+    attachment_cast!(
+        /// Get this attachment as a [`RegionAttachment`], or [`None`] if it's a different type.
+        ///
+        /// The region attachment borrows this attachment, so it cannot outlive it:
+        ///
+        /// ```compile_fail
+        /// # #[path="./test.rs"]
+        /// # mod test;
+        /// # let (skeleton, _) = test::TestAsset::spineboy().instance();
+        /// # let slot = skeleton.slots().find(|slot| slot.attachment().is_some()).unwrap();
+        /// let attachment = slot.attachment().unwrap().clone();
+        /// let region_attachment = attachment.as_region().unwrap();
+        /// drop(attachment);
+        /// println!("{}", region_attachment.width());
+        /// ```
+        as_region,
+        /// Get this attachment as a mutable [`RegionAttachment`], or [`None`] if it's a different
+        /// type.
+        as_region_mut,
+        RegionAttachment,
+        spRegionAttachment,
+        AttachmentType::Region
+    );
+    attachment_cast!(
+        /// Get this attachment as a [`BoundingBoxAttachment`], or [`None`] if it's a different type.
+        as_bounding_box,
+        /// Get this attachment as a mutable [`BoundingBoxAttachment`], or [`None`] if it's a
+        /// different type.
+        as_bounding_box_mut,
+        BoundingBoxAttachment,
+        spBoundingBoxAttachment,
+        AttachmentType::BoundingBox
+    );
+    attachment_cast!(
+        /// Get this attachment as a [`MeshAttachment`], or [`None`] if it's a different type.
+        as_mesh,
+        /// Get this attachment as a mutable [`MeshAttachment`], or [`None`] if it's a different
+        /// type.
+        as_mesh_mut,
+        MeshAttachment,
+        spMeshAttachment,
+        AttachmentType::Mesh
+    );
+    attachment_cast!(
+        /// Get this attachment as a [`PointAttachment`], or [`None`] if it's a different type.
+        as_point,
+        /// Get this attachment as a mutable [`PointAttachment`], or [`None`] if it's a different
+        /// type.
+        as_point_mut,
+        PointAttachment,
+        spPointAttachment,
+        AttachmentType::Point
+    );
+    attachment_cast!(
+        /// Get this attachment as a [`ClippingAttachment`], or [`None`] if it's a different type.
+        as_clipping,
+        /// Get this attachment as a mutable [`ClippingAttachment`], or [`None`] if it's a
+        /// different type.
+        as_clipping_mut,
+        ClippingAttachment,
+        spClippingAttachment,
+        AttachmentType::Clipping
+    );
 
     c_accessor_string!(
         /// The attachment's name.
@@ -127,6 +156,7 @@ impl Drop for Attachment {
 
 /// The type variants of an [`Attachment`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttachmentType {
     Region = 0,
     BoundingBox = 1,
@@ -1,12 +1,22 @@
+use std::{ptr::null_mut, sync::Arc};
+
 use crate::{
     c::{
-        spAtlasAttachmentLoader_create, spAttachmentLoader, spAttachmentLoader_createAttachment,
-        spAttachmentLoader_dispose,
+        _spAttachmentLoader_deinit, _spAttachmentLoader_init, _spAttachmentLoader_setError, c_char,
+        spAtlas, spAtlasAttachmentLoader, spAtlasAttachmentLoader_create, spAtlas_findRegion,
+        spAttachment, spAttachmentLoader, spAttachmentLoader_createAttachment,
+        spAttachmentLoader_dispose, spAttachmentType, spSkin, SP_ATTACHMENT_LINKED_MESH,
+        SP_ATTACHMENT_MESH, SP_ATTACHMENT_REGION,
     },
     c_interface::{NewFromPtr, SyncPtr},
     Atlas, Attachment, AttachmentType, RegionProps, Skin, SpineError,
 };
 
+#[cfg(not(feature = "spine38"))]
+use crate::c::spSequence;
+#[cfg(not(feature = "spine38"))]
+use std::ffi::{CStr, CString};
+
 /// A loader for creating custom attachments.
 ///
 /// Currently only supports [`Atlas`](`crate::Atlas`) based attachments.
@@ -90,9 +100,9 @@ impl AttachmentLoader {
         path: &str,
         props: &RegionProps,
     ) -> Result<Attachment, SpineError> {
-        let attachment = self.create_attachment(skin, AttachmentType::Region, name, path)?;
+        let mut attachment = self.create_attachment(skin, AttachmentType::Region, name, path)?;
 
-        let Some(mut region) = attachment.as_region() else {
+        let Some(mut region) = attachment.as_region_mut() else {
             return Err(SpineError::new_creation_failed("RegionAttachment"));
         };
 
@@ -113,3 +123,126 @@ impl Drop for AttachmentLoader {
         }
     }
 }
+
+/// An attachment loader which looks up regions in several atlases, using the first atlas which has
+/// the region. See [`SkeletonJson::new_with_atlases`](`crate::SkeletonJson`).
+///
+/// Allocated in Rust, so it must not be disposed by the Spine runtime.
+#[repr(C)]
+pub(crate) struct MultiAtlasAttachmentLoader {
+    super_0: spAttachmentLoader,
+    loaders: Vec<*mut spAtlasAttachmentLoader>,
+}
+
+impl MultiAtlasAttachmentLoader {
+    /// Create the loader on the heap. It must be freed with `Box::from_raw` once the skeleton
+    /// loader using it has been disposed.
+    pub(crate) fn new(atlases: &[Arc<Atlas>]) -> SyncPtr<Self> {
+        let mut loader = Box::new(Self {
+            super_0: spAttachmentLoader {
+                error1: null_mut(),
+                error2: null_mut(),
+                vtable: null_mut(),
+            },
+            loaders: atlases
+                .iter()
+                .map(|atlas| unsafe { spAtlasAttachmentLoader_create(atlas.c_ptr()) })
+                .collect(),
+        });
+        unsafe {
+            _spAttachmentLoader_init(
+                &mut loader.super_0,
+                Some(multi_atlas_attachment_loader_dispose),
+                Some(multi_atlas_attachment_loader_create_attachment),
+                None,
+                None,
+            );
+        }
+        SyncPtr(Box::into_raw(loader))
+    }
+}
+
+impl Drop for MultiAtlasAttachmentLoader {
+    fn drop(&mut self) {
+        unsafe {
+            for loader in &self.loaders {
+                spAttachmentLoader_dispose(&mut (**loader).super_0);
+            }
+            _spAttachmentLoader_deinit(&mut self.super_0);
+        }
+    }
+}
+
+// Disposed by `Drop` instead.
+const extern "C" fn multi_atlas_attachment_loader_dispose(_loader: *mut spAttachmentLoader) {}
+
+unsafe extern "C" fn multi_atlas_attachment_loader_create_attachment(
+    loader: *mut spAttachmentLoader,
+    skin: *mut spSkin,
+    attachment_type: spAttachmentType,
+    name: *const c_char,
+    path: *const c_char,
+    #[cfg(not(feature = "spine38"))] sequence: *mut spSequence,
+) -> *mut spAttachment {
+    let loader = loader.cast::<MultiAtlasAttachmentLoader>();
+    // Only call the loader of an atlas with the region. An atlas loader which does not find a
+    // mesh region leaks the mesh it created, so the loaders cannot simply be tried in turn.
+    let atlas_loader = if matches!(
+        attachment_type,
+        SP_ATTACHMENT_REGION | SP_ATTACHMENT_MESH | SP_ATTACHMENT_LINKED_MESH
+    ) {
+        (*loader).loaders.iter().find(|atlas_loader| {
+            atlas_has_region(
+                (***atlas_loader).atlas,
+                path,
+                #[cfg(not(feature = "spine38"))]
+                sequence,
+            )
+        })
+    } else {
+        (*loader).loaders.first()
+    };
+    let Some(atlas_loader) = atlas_loader else {
+        _spAttachmentLoader_setError(&mut (*loader).super_0, c"Region not found: ".as_ptr(), path);
+        return null_mut();
+    };
+    let atlas_loader = &mut (**atlas_loader).super_0;
+    let attachment = spAttachmentLoader_createAttachment(
+        atlas_loader,
+        skin,
+        attachment_type,
+        name,
+        path,
+        #[cfg(not(feature = "spine38"))]
+        sequence,
+    );
+    if attachment.is_null() {
+        _spAttachmentLoader_setError(
+            &mut (*loader).super_0,
+            atlas_loader.error1,
+            atlas_loader.error2,
+        );
+    }
+    attachment
+}
+
+/// Whether `atlas` has the region at `path`, or every region of `sequence` if there is one.
+unsafe fn atlas_has_region(
+    atlas: *mut spAtlas,
+    path: *const c_char,
+    #[cfg(not(feature = "spine38"))] sequence: *mut spSequence,
+) -> bool {
+    #[cfg(not(feature = "spine38"))]
+    if !sequence.is_null() {
+        let base_path = CStr::from_ptr(path).to_string_lossy();
+        let digits = (*sequence).digits as usize;
+        return (0..(*(*sequence).regions).size).all(|index| {
+            let frame = (*sequence).start + index;
+            let Ok(frame_path) = CString::new(format!("{base_path}{frame:0digits$}")) else {
+                return false;
+            };
+            !spAtlas_findRegion(atlas, frame_path.as_ptr()).is_null()
+        });
+    }
+    !spAtlas_findRegion(atlas, path).is_null()
+}
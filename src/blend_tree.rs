@@ -0,0 +1,474 @@
+//! Provides [`BlendTree`], which blends several animations together with weights computed from
+//! one or two parameters.
+//!
+//! Tracks in an [`AnimationState`](`crate::AnimationState`) can only be layered on top of each
+//! other. A blend tree instead samples a set of animations ("motions") placed in a 1D or 2D
+//! parameter space, such as walk and run placed by speed, and applies them to a skeleton with
+//! weights based on where the parameter lies between them.
+//!
+//! All motions are played in sync by normalized time, so for example the footsteps of a walk and
+//! run animation with different durations stay aligned while blending.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use rusty_spine::{blend_tree::*, *};
+//!
+//! # let atlas = Arc::new(Atlas::new_from_file("assets/spineboy/export/spineboy.atlas").unwrap());
+//! # let skeleton_json = SkeletonJson::new(atlas);
+//! # let skeleton_data = Arc::new(
+//! #     skeleton_json
+//! #         .read_skeleton_data_file("assets/spineboy/export/spineboy-pro.json")
+//! #         .unwrap(),
+//! # );
+//! let mut skeleton = Skeleton::new(skeleton_data.clone());
+//! let mut locomotion =
+//!     BlendTree::new_1d(skeleton_data, &[("idle", 0.), ("walk", 1.), ("run", 3.)]).unwrap();
+//!
+//! // Each frame
+//! locomotion.set_parameter(2.);
+//! locomotion.update(0.016);
+//! locomotion.apply(&mut skeleton, 1., MixBlend::Replace);
+//! skeleton.update_world_transform();
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    animation::{MixBlend, MixDirection},
+    error::SpineError,
+    Skeleton, SkeletonData,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlendSpace {
+    OneD,
+    TwoD,
+}
+
+#[derive(Debug)]
+struct Motion {
+    name: String,
+    animation_index: usize,
+    duration: f32,
+    position: [f32; 2],
+}
+
+/// A set of animations blended by a 1D or 2D parameter, see the [module docs](`self`).
+#[derive(Debug)]
+pub struct BlendTree {
+    skeleton_data: Arc<SkeletonData>,
+    space: BlendSpace,
+    motions: Vec<Motion>,
+    weights: Vec<f32>,
+    parameter: [f32; 2],
+    phase: f32,
+    last_phase: f32,
+}
+
+impl BlendTree {
+    /// Create a blend tree where each animation is placed at a threshold on a single axis.
+    ///
+    /// Parameters between two thresholds blend linearly between the neighboring animations.
+    /// Parameters outside of all thresholds use the nearest animation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if an animation does not exist, or
+    /// [`SpineError::CreationFailed`] if no animations are given.
+    pub fn new_1d(
+        skeleton_data: Arc<SkeletonData>,
+        motions: &[(&str, f32)],
+    ) -> Result<Self, SpineError> {
+        let motions = motions
+            .iter()
+            .map(|(name, threshold)| (*name, [*threshold, 0.]))
+            .collect::<Vec<_>>();
+        Self::new(skeleton_data, BlendSpace::OneD, &motions)
+    }
+
+    /// Create a blend tree where each animation is placed at a direction and distance from the
+    /// origin, for example strafe animations placed by velocity.
+    ///
+    /// The parameter blends between the two animations adjacent to its direction. An animation
+    /// placed at the origin (such as idle) is blended in as the parameter gets closer to the
+    /// origin than the adjacent animations.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if an animation does not exist, or
+    /// [`SpineError::CreationFailed`] if no animations are given.
+    pub fn new_2d(
+        skeleton_data: Arc<SkeletonData>,
+        motions: &[(&str, [f32; 2])],
+    ) -> Result<Self, SpineError> {
+        Self::new(skeleton_data, BlendSpace::TwoD, motions)
+    }
+
+    fn new(
+        skeleton_data: Arc<SkeletonData>,
+        space: BlendSpace,
+        motions: &[(&str, [f32; 2])],
+    ) -> Result<Self, SpineError> {
+        if motions.is_empty() {
+            return Err(SpineError::new_creation_failed("BlendTree without motions"));
+        }
+        let motions = motions
+            .iter()
+            .map(|(name, position)| {
+                let (animation_index, animation) = skeleton_data
+                    .animations()
+                    .enumerate()
+                    .find(|(_, animation)| animation.name() == *name)
+                    .ok_or_else(|| SpineError::new_not_found("Animation", name))?;
+                Ok(Motion {
+                    name: (*name).to_owned(),
+                    animation_index,
+                    duration: animation.duration(),
+                    position: *position,
+                })
+            })
+            .collect::<Result<Vec<_>, SpineError>>()?;
+        let mut blend_tree = Self {
+            skeleton_data,
+            space,
+            weights: vec![0.; motions.len()],
+            motions,
+            parameter: [0., 0.],
+            phase: 0.,
+            last_phase: 0.,
+        };
+        blend_tree.compute_weights();
+        Ok(blend_tree)
+    }
+
+    /// Set the parameter of a 1D blend tree. For 2D blend trees, this sets the x axis.
+    pub fn set_parameter(&mut self, value: f32) {
+        self.parameter[0] = value;
+        self.compute_weights();
+    }
+
+    /// Set the parameter of a 2D blend tree.
+    pub fn set_parameter_2d(&mut self, x: f32, y: f32) {
+        self.parameter = [x, y];
+        self.compute_weights();
+    }
+
+    /// The current parameter, with y always 0 for 1D blend trees.
+    #[must_use]
+    pub const fn parameter(&self) -> [f32; 2] {
+        self.parameter
+    }
+
+    /// The animation names and their current weights, which sum to 1.
+    pub fn weights(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.motions
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(motion, weight)| (motion.name.as_str(), *weight))
+    }
+
+    /// The normalized time (0 to 1) shared by all animations in the blend tree.
+    #[must_use]
+    pub const fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Set the normalized time, for example to restart the blend tree from the beginning.
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase.rem_euclid(1.);
+        self.last_phase = self.phase;
+    }
+
+    /// The duration of one loop of the blend tree, which is the weighted average of the
+    /// animation durations.
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.motions
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(motion, weight)| motion.duration * weight)
+            .sum()
+    }
+
+    /// Advance the normalized time of all animations.
+    pub fn update(&mut self, delta: f32) {
+        self.last_phase = self.phase;
+        let duration = self.duration();
+        if duration > 0. {
+            self.phase = (self.phase + delta / duration).rem_euclid(1.);
+        }
+    }
+
+    /// Apply the weighted animations to the skeleton, mixed over the current pose by `alpha`.
+    ///
+    /// With [`MixBlend::Add`], each animation is added to the current pose scaled by its weight.
+    /// Otherwise, the first animation is applied with `blend` and the others are blended over it
+    /// using [`MixBlend::Replace`], so the result is the weighted average of all animations.
+    pub fn apply(&self, skeleton: &mut Skeleton, alpha: f32, blend: MixBlend) {
+        let total_weight: f32 = self.weights.iter().sum();
+        if total_weight <= 0. {
+            return;
+        }
+        let mut cumulative_weight = 0.;
+        let mut first = true;
+        for (motion, weight) in self.motions.iter().zip(self.weights.iter()) {
+            if *weight <= 0. {
+                continue;
+            }
+            let Some(animation) = self
+                .skeleton_data
+                .animation_at_index(motion.animation_index)
+            else {
+                continue;
+            };
+            // Each replace applies `pose + (animation - pose) * alpha`, so these alphas leave
+            // `alpha * weight / total_weight` of every animation and `1 - alpha` of the
+            // original pose.
+            cumulative_weight += weight;
+            let (motion_alpha, motion_blend) = if blend == MixBlend::Add {
+                (alpha * weight / total_weight, MixBlend::Add)
+            } else {
+                let remaining = (1. - alpha) * total_weight + alpha * cumulative_weight;
+                let motion_blend = if first { blend } else { MixBlend::Replace };
+                (alpha * weight / remaining, motion_blend)
+            };
+            first = false;
+            animation.apply(
+                skeleton,
+                self.last_phase * motion.duration,
+                self.phase * motion.duration,
+                true,
+                motion_alpha,
+                motion_blend,
+                MixDirection::In,
+            );
+        }
+    }
+
+    fn compute_weights(&mut self) {
+        self.weights.iter_mut().for_each(|weight| *weight = 0.);
+        match self.space {
+            BlendSpace::OneD => self.compute_weights_1d(),
+            BlendSpace::TwoD => self.compute_weights_2d(),
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    fn compute_weights_1d(&mut self) {
+        let parameter = self.parameter[0];
+        let mut below: Option<usize> = None;
+        let mut above: Option<usize> = None;
+        for (index, motion) in self.motions.iter().enumerate() {
+            let threshold = motion.position[0];
+            if threshold <= parameter
+                && below.map_or(true, |below| threshold > self.motions[below].position[0])
+            {
+                below = Some(index);
+            }
+            if threshold >= parameter
+                && above.map_or(true, |above| threshold < self.motions[above].position[0])
+            {
+                above = Some(index);
+            }
+        }
+        match (below, above) {
+            (Some(below), Some(above)) if below != above => {
+                let low = self.motions[below].position[0];
+                let high = self.motions[above].position[0];
+                let t = (parameter - low) / (high - low);
+                self.weights[below] = 1. - t;
+                self.weights[above] = t;
+            }
+            (Some(index), _) | (None, Some(index)) => self.weights[index] = 1.,
+            (None, None) => {}
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    fn compute_weights_2d(&mut self) {
+        let [x, y] = self.parameter;
+        let center = self
+            .motions
+            .iter()
+            .position(|motion| motion.position == [0., 0.]);
+        let magnitude = x.hypot(y);
+        if magnitude <= f32::EPSILON {
+            match center {
+                Some(center) => self.weights[center] = 1.,
+                None => self.weights.iter_mut().for_each(|weight| *weight = 1.),
+            }
+            self.normalize_weights();
+            return;
+        }
+
+        // Find the motions closest in angle on either side of the parameter direction.
+        let angle = y.atan2(x);
+        let mut clockwise: Option<(usize, f32)> = None;
+        let mut counter_clockwise: Option<(usize, f32)> = None;
+        for (index, motion) in self.motions.iter().enumerate() {
+            if Some(index) == center {
+                continue;
+            }
+            let [motion_x, motion_y] = motion.position;
+            let difference = (motion_y.atan2(motion_x) - angle).rem_euclid(std::f32::consts::TAU);
+            if counter_clockwise.map_or(true, |(_, best)| difference < best) {
+                counter_clockwise = Some((index, difference));
+            }
+            let difference = std::f32::consts::TAU - difference;
+            if clockwise.map_or(true, |(_, best)| difference < best) {
+                clockwise = Some((index, difference));
+            }
+        }
+        let (Some((mut a, _)), Some((b, b_difference))) = (clockwise, counter_clockwise) else {
+            if let Some(center) = center {
+                self.weights[center] = 1.;
+            }
+            return;
+        };
+        if b_difference <= f32::EPSILON {
+            a = b;
+        }
+
+        // Solve `parameter = weight_a * a + weight_b * b`.
+        let [ax, ay] = self.motions[a].position;
+        let [bx, by] = self.motions[b].position;
+        let determinant = ax * by - ay * bx;
+        let (mut weight_a, mut weight_b) = if a == b || determinant.abs() <= f32::EPSILON {
+            // The parameter lines up with a single motion, project onto it.
+            let length_squared = ax * ax + ay * ay;
+            (((x * ax + y * ay) / length_squared).max(0.), 0.)
+        } else {
+            (
+                ((x * by - y * bx) / determinant).max(0.),
+                ((ax * y - ay * x) / determinant).max(0.),
+            )
+        };
+        let sum = weight_a + weight_b;
+        if sum <= f32::EPSILON {
+            // The parameter points away from all motions.
+            match center {
+                Some(center) => self.weights[center] = 1.,
+                None => {
+                    self.weights[a] = 0.5;
+                    self.weights[b] += 0.5;
+                }
+            }
+            self.normalize_weights();
+            return;
+        }
+        if sum > 1. || center.is_none() {
+            weight_a /= sum;
+            weight_b /= sum;
+        } else if let Some(center) = center {
+            self.weights[center] = 1. - sum;
+        }
+        self.weights[a] += weight_a;
+        if a != b {
+            self.weights[b] += weight_b;
+        }
+        self.normalize_weights();
+    }
+
+    fn normalize_weights(&mut self) {
+        let total: f32 = self.weights.iter().sum();
+        if total > 0. {
+            self.weights.iter_mut().for_each(|weight| *weight /= total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{test::TestAsset, Skeleton};
+
+    use super::*;
+
+    fn weight(blend_tree: &BlendTree, name: &str) -> f32 {
+        blend_tree
+            .weights()
+            .find(|(motion, _)| *motion == name)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn blend_tree_1d_weights() {
+        let skeleton_data = Arc::new(TestAsset::spineboy().skeleton_data());
+        let mut blend_tree =
+            BlendTree::new_1d(skeleton_data, &[("idle", 0.), ("walk", 1.), ("run", 3.)]).unwrap();
+        assert_eq!(weight(&blend_tree, "idle"), 1.);
+        blend_tree.set_parameter(2.);
+        assert_eq!(weight(&blend_tree, "idle"), 0.);
+        assert_eq!(weight(&blend_tree, "walk"), 0.5);
+        assert_eq!(weight(&blend_tree, "run"), 0.5);
+        blend_tree.set_parameter(10.);
+        assert_eq!(weight(&blend_tree, "run"), 1.);
+        blend_tree.set_parameter(-1.);
+        assert_eq!(weight(&blend_tree, "idle"), 1.);
+    }
+
+    #[test]
+    fn blend_tree_2d_weights() {
+        let skeleton_data = Arc::new(TestAsset::spineboy().skeleton_data());
+        let mut blend_tree = BlendTree::new_2d(
+            skeleton_data,
+            &[
+                ("idle", [0., 0.]),
+                ("walk", [1., 0.]),
+                ("run", [0., 1.]),
+                ("jump", [-1., 0.]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(weight(&blend_tree, "idle"), 1.);
+        blend_tree.set_parameter_2d(0.5, 0.);
+        assert!((weight(&blend_tree, "idle") - 0.5).abs() < 1e-5);
+        assert!((weight(&blend_tree, "walk") - 0.5).abs() < 1e-5);
+        blend_tree.set_parameter_2d(1., 1.);
+        assert!((weight(&blend_tree, "walk") - 0.5).abs() < 1e-5);
+        assert!((weight(&blend_tree, "run") - 0.5).abs() < 1e-5);
+        assert_eq!(weight(&blend_tree, "jump"), 0.);
+        let total: f32 = blend_tree.weights().map(|(_, weight)| weight).sum();
+        assert!((total - 1.).abs() < 1e-5);
+    }
+
+    /// A blend tree fully weighted to one animation must match applying that animation directly.
+    #[test]
+    fn blend_tree_apply_single() {
+        let skeleton_data = Arc::new(TestAsset::spineboy().skeleton_data());
+        let mut blend_tree =
+            BlendTree::new_1d(skeleton_data.clone(), &[("walk", 0.), ("run", 1.)]).unwrap();
+        blend_tree.set_parameter(1.);
+        blend_tree.update(0.2);
+        let mut blended = Skeleton::new(skeleton_data.clone());
+        blend_tree.apply(&mut blended, 1., MixBlend::Setup);
+        blended.update_world_transform();
+
+        let mut direct = Skeleton::new(skeleton_data.clone());
+        let run = skeleton_data.find_animation("run").unwrap();
+        run.apply(
+            &mut direct,
+            0.,
+            blend_tree.phase() * run.duration(),
+            true,
+            1.,
+            MixBlend::Setup,
+            MixDirection::In,
+        );
+        direct.update_world_transform();
+        for (a, b) in blended.bones().zip(direct.bones()) {
+            assert!((a.world_x() - b.world_x()).abs() < 1e-3);
+            assert!((a.world_y() - b.world_y()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn blend_tree_missing_animation() {
+        let skeleton_data = Arc::new(TestAsset::spineboy().skeleton_data());
+        assert!(BlendTree::new_1d(skeleton_data.clone(), &[("fly", 0.)]).is_err());
+        assert!(BlendTree::new_1d(skeleton_data, &[]).is_err());
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
         spBone_updateWorldTransform, spBone_updateWorldTransformWith, spBone_worldToLocal,
         spBone_worldToLocalRotation, spSkeleton, spTransformMode,
     },
-    c_interface::{NewFromPtr, SyncPtr},
+    c_interface::{CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     Skeleton,
 };
 
@@ -181,10 +181,67 @@ impl Bone {
         }
     }
 
-    /// Create a persistent [`BoneHandle`] to this [`Bone`].
+    /// Sets the world transform, then the applied transform to match it, using the parent bone.
+    ///
+    /// The rotation is applied to both axes, keeping any shear or reflection of the current world
+    /// transform. Child bones are not updated, see
+    /// [`Skeleton::set_bone_world_transform`] to update them as well.
+    pub fn set_world_transform(&mut self, transform: &BoneWorldTransform) {
+        let rotation_x = self.world_rotation_x();
+        let rotation_y = if self.world_scale_y() == 0. {
+            rotation_x + 90.
+        } else {
+            self.world_rotation_y()
+        } + transform.rotation
+            - rotation_x;
+        let (sin_x, cos_x) = transform.rotation.to_radians().sin_cos();
+        let (sin_y, cos_y) = rotation_y.to_radians().sin_cos();
+        self.set_a(cos_x * transform.scale_x);
+        self.set_c(sin_x * transform.scale_x);
+        self.set_b(cos_y * transform.scale_y);
+        self.set_d(sin_y * transform.scale_y);
+        self.set_world_x(transform.x);
+        self.set_world_y(transform.y);
+        self.update_applied_transform();
+        if unsafe { self.c_ptr_ref().parent.is_null() } {
+            self.update_root_applied_transform();
+        }
+    }
+
+    /// [`update_applied_transform`](`Self::update_applied_transform`) ignores the skeleton's scale
+    /// and [`is_y_down`](`Self::is_y_down`) for the root bone, so undo them here.
+    fn update_root_applied_transform(&mut self) {
+        let skeleton = unsafe { &*self.c_ptr_ref().skeleton };
+        let scale_x = skeleton.scaleX;
+        let scale_y = skeleton.scaleY * if Self::is_y_down() { -1. } else { 1. };
+        if scale_x == 0. || scale_y == 0. {
+            return;
+        }
+        let a = self.a() / scale_x;
+        let b = self.b() / scale_x;
+        let c = self.c() / scale_y;
+        let d = self.d() / scale_y;
+        self.set_applied_x((self.world_x() - skeleton.x) / scale_x);
+        self.set_applied_y((self.world_y() - skeleton.y) / scale_y);
+        self.set_applied_rotation(c.atan2(a).to_degrees());
+        self.set_applied_scale_x(a.hypot(c));
+        self.set_applied_scale_y(b.hypot(d));
+        self.set_applied_shear_x(0.);
+        self.set_applied_shear_y((a * b + c * d).atan2(a * d - b * c).to_degrees());
+    }
+
+    /// Create a persistent [`BoneHandle`] to this [`Bone`], owned by `skeleton`.
+    ///
+    /// If `skeleton` does not own this bone, the handle never resolves.
     #[must_use]
-    pub fn handle(&self) -> BoneHandle {
-        BoneHandle::new(self.c_ptr(), unsafe { self.c_ptr_mut().skeleton })
+    pub fn handle(&self, skeleton: &Skeleton) -> BoneHandle {
+        let c_skeleton = unsafe { self.c_ptr_mut().skeleton };
+        let skeleton_id = if c_skeleton == skeleton.c_ptr() {
+            skeleton.handle_id()
+        } else {
+            0
+        };
+        BoneHandle::new(self.c_ptr(), c_skeleton, skeleton_id)
     }
 
     c_accessor_mut!(
@@ -387,7 +444,7 @@ impl Bone {
         ///             name = bone.data().name()
         ///         );
         ///         for child in bone.children() {
-        ///             traverse_bones(child.handle(), skeleton, ident + 2);
+        ///             traverse_bones(child.handle(skeleton), skeleton, ident + 2);
         ///         }
         ///     }
         /// }
@@ -408,12 +465,16 @@ impl Bone {
         children_count
     );
 
+    /// Flip every skeleton in the process on the Y axis when computing world transforms, for a
+    /// coordinate system where Y points down. To flip a single skeleton, see
+    /// [`Skeleton::set_y_down`](`crate::Skeleton::set_y_down`).
     pub fn set_y_down(y_down: bool) {
         unsafe {
             spBone_setYDown(i32::from(y_down));
         }
     }
 
+    /// Whether every skeleton is flipped on the Y axis, see [`Bone::set_y_down`].
     #[must_use]
     pub fn is_y_down() -> bool {
         unsafe { spBone_isYDown() != 0 }
@@ -599,6 +660,64 @@ c_handle_decl!(
     spSkeleton
 );
 
+impl<'a> CTmpRef<'a, Skeleton, Bone> {
+    #[must_use]
+    pub fn handle(&self) -> BoneHandle {
+        BoneHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
+    }
+}
+
+impl<'a> CTmpMut<'a, Skeleton, Bone> {
+    #[must_use]
+    pub fn handle(&self) -> BoneHandle {
+        BoneHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
+    }
+}
+
+/// A bone's position, rotation, and scale in world coordinates, see
+/// [`Skeleton::set_bone_world_transform`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoneWorldTransform {
+    /// The world X position.
+    pub x: f32,
+    /// The world Y position.
+    pub y: f32,
+    /// The world rotation of the X axis in degrees, see [`Bone::world_rotation_x`].
+    pub rotation: f32,
+    /// The world scale of the X axis, see [`Bone::world_scale_x`].
+    pub scale_x: f32,
+    /// The world scale of the Y axis, see [`Bone::world_scale_y`].
+    pub scale_y: f32,
+}
+
+impl BoneWorldTransform {
+    /// The current world transform of a bone, for changing only some of its values.
+    #[must_use]
+    pub fn from_bone(bone: &Bone) -> Self {
+        Self {
+            x: bone.world_x(),
+            y: bone.world_y(),
+            rotation: bone.world_rotation_x(),
+            scale_x: bone.world_scale_x(),
+            scale_y: bone.world_scale_y(),
+        }
+    }
+}
+
+/// When a world transform set with [`Skeleton::set_bone_world_transform`] is applied.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorldOverrideStage {
+    /// Set the bone's local transform and update the whole skeleton, so constraints are applied
+    /// on top of the override and bones constrained to the bone follow it.
+    #[default]
+    BeforeConstraints,
+    /// Set the bone's applied transform and update only the bone and its descendants, keeping
+    /// the results of constraints for every other bone.
+    AfterConstraints,
+}
+
 /// Static bone data imported from Spine.
 #[derive(Debug)]
 pub struct BoneData {
@@ -668,6 +787,8 @@ impl BoneData {
 /// The transform mode for how bones are affected by their parents.
 ///
 /// See [`BoneData::transform_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransformMode {
     Normal = 0,
     OnlyTranslation = 1,
@@ -0,0 +1,311 @@
+use std::collections::VecDeque;
+
+use crate::{
+    bone::Bone,
+    c::spBone,
+    c_interface::{CTmpRef, NewFromPtr},
+    skeleton::Skeleton,
+};
+
+/// Queries on the bone hierarchy of a [`Skeleton`].
+///
+/// Bones are referred to by their index in [`Skeleton::bones`], which can be looked up by name in
+/// constant time with [`Skeleton::find_bone_index`]. Invalid indices are treated as a bone with no
+/// parent or children.
+///
+/// ```
+/// # #[path="./test.rs"]
+/// # mod test;
+/// # let (skeleton, _) = test::TestAsset::spineboy().instance();
+/// let gun = skeleton.find_bone_index("gun").unwrap();
+/// for bone in skeleton.bone_path(gun) {
+///     println!("{}", bone.data().name());
+/// }
+/// ```
+impl Skeleton {
+    fn c_bone(&self, bone_index: usize) -> *mut spBone {
+        if bone_index < self.bones_count() {
+            unsafe { *self.c_ptr_ref().bones.add(bone_index) }
+        } else {
+            std::ptr::null_mut()
+        }
+    }
+
+    /// The parent, grandparent, and so on of a bone, ending with the root bone.
+    #[must_use]
+    pub fn bone_ancestors(&self, bone_index: usize) -> BoneAncestors<'_> {
+        let c_bone = self.c_bone(bone_index);
+        BoneAncestors {
+            skeleton: self,
+            c_bone: if c_bone.is_null() {
+                c_bone
+            } else {
+                unsafe { (*c_bone).parent }
+            },
+        }
+    }
+
+    /// The children, grandchildren, and so on of a bone in depth-first order, not including the
+    /// bone itself.
+    #[must_use]
+    pub fn bone_descendants(&self, bone_index: usize) -> BonesDepthFirst<'_> {
+        let mut bones = self.bones_depth_first(bone_index);
+        if let Some(c_bone) = bones.stack.pop() {
+            bones.push_children(c_bone);
+        }
+        bones
+    }
+
+    /// A bone followed by its descendants in depth-first order, where each bone is followed by
+    /// its children's subtrees before its next sibling. Use an index of 0 to traverse the whole
+    /// skeleton.
+    #[must_use]
+    pub fn bones_depth_first(&self, bone_index: usize) -> BonesDepthFirst<'_> {
+        let c_bone = self.c_bone(bone_index);
+        BonesDepthFirst {
+            skeleton: self,
+            stack: if c_bone.is_null() {
+                vec![]
+            } else {
+                vec![c_bone]
+            },
+        }
+    }
+
+    /// A bone followed by its descendants in breadth-first order, where every bone of a depth
+    /// comes before any bone of the next. Use an index of 0 to traverse the whole skeleton.
+    #[must_use]
+    pub fn bones_breadth_first(&self, bone_index: usize) -> BonesBreadthFirst<'_> {
+        let c_bone = self.c_bone(bone_index);
+        let mut queue = VecDeque::new();
+        if !c_bone.is_null() {
+            queue.push_back(c_bone);
+        }
+        BonesBreadthFirst {
+            skeleton: self,
+            queue,
+        }
+    }
+
+    /// The bones from the root bone down to and including a bone, or an empty list if the index is
+    /// invalid.
+    #[must_use]
+    pub fn bone_path(&self, bone_index: usize) -> Vec<CTmpRef<'_, Self, Bone>> {
+        let mut path: Vec<CTmpRef<Self, Bone>> = self
+            .bone_at_index(bone_index)
+            .into_iter()
+            .chain(self.bone_ancestors(bone_index))
+            .collect();
+        path.reverse();
+        path
+    }
+
+    /// Whether a bone is the parent, grandparent, and so on of another bone. A bone is not its own
+    /// ancestor.
+    #[must_use]
+    pub fn is_bone_ancestor(&self, ancestor_index: usize, bone_index: usize) -> bool {
+        self.bone_ancestors(bone_index)
+            .any(|bone| bone.data().index() == ancestor_index)
+    }
+
+    /// The deepest bone which is or is an ancestor of both bones, or [`None`] if either index is
+    /// invalid.
+    #[must_use]
+    pub fn lowest_common_ancestor(
+        &self,
+        bone_index_a: usize,
+        bone_index_b: usize,
+    ) -> Option<CTmpRef<'_, Self, Bone>> {
+        let path_a = self.bone_path(bone_index_a);
+        let path_b = self.bone_path(bone_index_b);
+        path_a
+            .into_iter()
+            .zip(path_b)
+            .take_while(|(a, b)| a.c_ptr() == b.c_ptr())
+            .last()
+            .map(|(a, _)| a)
+    }
+}
+
+/// An iterator over the ancestors of a bone, see [`Skeleton::bone_ancestors`].
+pub struct BoneAncestors<'a> {
+    skeleton: &'a Skeleton,
+    c_bone: *mut spBone,
+}
+
+impl<'a> Iterator for BoneAncestors<'a> {
+    type Item = CTmpRef<'a, Skeleton, Bone>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.c_bone.is_null() {
+            None
+        } else {
+            let c_bone = self.c_bone;
+            self.c_bone = unsafe { (*c_bone).parent };
+            Some(CTmpRef::new(self.skeleton, unsafe {
+                Bone::new_from_ptr(c_bone)
+            }))
+        }
+    }
+}
+
+/// A depth-first iterator over a bone's subtree, see [`Skeleton::bones_depth_first`].
+pub struct BonesDepthFirst<'a> {
+    skeleton: &'a Skeleton,
+    stack: Vec<*mut spBone>,
+}
+
+impl<'a> BonesDepthFirst<'a> {
+    fn push_children(&mut self, c_bone: *mut spBone) {
+        let children = unsafe { children(c_bone) };
+        self.stack.extend(children.iter().rev());
+    }
+}
+
+impl<'a> Iterator for BonesDepthFirst<'a> {
+    type Item = CTmpRef<'a, Skeleton, Bone>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c_bone = self.stack.pop()?;
+        self.push_children(c_bone);
+        Some(CTmpRef::new(self.skeleton, unsafe {
+            Bone::new_from_ptr(c_bone)
+        }))
+    }
+}
+
+/// A breadth-first iterator over a bone's subtree, see [`Skeleton::bones_breadth_first`].
+pub struct BonesBreadthFirst<'a> {
+    skeleton: &'a Skeleton,
+    queue: VecDeque<*mut spBone>,
+}
+
+impl<'a> Iterator for BonesBreadthFirst<'a> {
+    type Item = CTmpRef<'a, Skeleton, Bone>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c_bone = self.queue.pop_front()?;
+        self.queue.extend(unsafe { children(c_bone) });
+        Some(CTmpRef::new(self.skeleton, unsafe {
+            Bone::new_from_ptr(c_bone)
+        }))
+    }
+}
+
+unsafe fn children<'a>(c_bone: *mut spBone) -> &'a [*mut spBone] {
+    let count = (*c_bone).childrenCount as usize;
+    if count == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts((*c_bone).children, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::TestAsset;
+
+    use super::*;
+
+    #[test]
+    fn bone_hierarchy_traversal() {
+        for asset in TestAsset::all() {
+            let (skeleton, _) = asset.instance();
+            let bones_count = skeleton.bones_count();
+            let index = |bone: CTmpRef<Skeleton, Bone>| bone.data().index();
+
+            for order in [
+                skeleton.bones_depth_first(0).map(index).collect::<Vec<_>>(),
+                skeleton
+                    .bones_breadth_first(0)
+                    .map(index)
+                    .collect::<Vec<_>>(),
+            ] {
+                let mut sorted = order.clone();
+                sorted.sort_unstable();
+                assert_eq!(sorted, (0..bones_count).collect::<Vec<_>>());
+                for (position, &bone_index) in order.iter().enumerate() {
+                    if let Some(parent) = skeleton.bone_ancestors(bone_index).next() {
+                        let parent_index = parent.data().index();
+                        assert!(order[..position].contains(&parent_index));
+                    }
+                }
+            }
+
+            let depths: Vec<usize> = skeleton
+                .bones_breadth_first(0)
+                .map(|bone| skeleton.bone_ancestors(bone.data().index()).count())
+                .collect();
+            assert!(depths.windows(2).all(|depths| depths[0] <= depths[1]));
+
+            for bone_index in 0..bones_count {
+                let descendants: Vec<usize> =
+                    skeleton.bone_descendants(bone_index).map(index).collect();
+                assert_eq!(
+                    descendants,
+                    skeleton
+                        .bones_depth_first(bone_index)
+                        .skip(1)
+                        .map(index)
+                        .collect::<Vec<_>>()
+                );
+                for descendant in descendants {
+                    assert!(skeleton.is_bone_ancestor(bone_index, descendant));
+                    assert!(!skeleton.is_bone_ancestor(descendant, bone_index));
+                }
+            }
+            assert!(skeleton.bones_depth_first(bones_count).next().is_none());
+            assert!(skeleton.bone_ancestors(bones_count).next().is_none());
+        }
+    }
+
+    #[test]
+    fn bone_hierarchy_queries() {
+        let (skeleton, _) = TestAsset::spineboy().instance();
+        let find = |name: &str| skeleton.find_bone_index(name).unwrap();
+        let name = |bone: &CTmpRef<Skeleton, Bone>| bone.data().name().to_owned();
+
+        let path: Vec<String> = skeleton.bone_path(find("gun")).iter().map(name).collect();
+        assert_eq!(
+            path,
+            [
+                "root",
+                "hip",
+                "torso",
+                "torso2",
+                "torso3",
+                "back-shoulder",
+                "rear-upper-arm",
+                "rear-bracer",
+                "gun"
+            ]
+        );
+        assert!(skeleton.bone_path(skeleton.bones_count()).is_empty());
+
+        assert!(skeleton.is_bone_ancestor(find("root"), find("gun")));
+        assert!(!skeleton.is_bone_ancestor(find("gun"), find("gun")));
+        assert!(!skeleton.is_bone_ancestor(find("gun"), find("root")));
+
+        let lowest_common_ancestor = |a: &str, b: &str| {
+            skeleton
+                .lowest_common_ancestor(find(a), find(b))
+                .map(|bone| name(&bone))
+        };
+        assert_eq!(lowest_common_ancestor("gun", "gun").as_deref(), Some("gun"));
+        assert_eq!(
+            lowest_common_ancestor("root", "gun").as_deref(),
+            Some("root")
+        );
+        assert_eq!(
+            lowest_common_ancestor("front-foot", "rear-foot").as_deref(),
+            Some("hip")
+        );
+        assert_eq!(
+            lowest_common_ancestor("gun", "head").as_deref(),
+            Some("torso3")
+        );
+        assert!(skeleton
+            .lowest_common_ancestor(find("gun"), skeleton.bones_count())
+            .is_none());
+    }
+}
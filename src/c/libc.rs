@@ -92,7 +92,7 @@ unsafe extern "C" fn spine_strrchr(s: *const c_char, c: c_int) -> *mut c_char {
 
 #[no_mangle]
 unsafe extern "C" fn spine_rand() -> c_int {
-    libc::rand()
+    crate::random::next()
 }
 
 #[no_mangle]
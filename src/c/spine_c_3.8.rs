@@ -4793,16 +4793,20 @@ pub unsafe extern "C" fn _spAnimationState_disposeTrackEntries(
     }
 }
 #[no_mangle]
-pub unsafe extern "C" fn spAnimationState_create(
-    mut data: *mut spAnimationStateData,
-) -> *mut spAnimationState {
-    let mut internal: *mut _spAnimationState = 0 as *mut _spAnimationState;
-    let mut self_0: *mut spAnimationState = 0 as *mut spAnimationState;
+pub unsafe extern "C" fn spAnimationState_initStatics() {
     if SP_EMPTY_ANIMATION.is_null() {
         SP_EMPTY_ANIMATION = 1 as c_int as *mut spAnimation;
         SP_EMPTY_ANIMATION =
             spAnimation_create(b"<empty>\0" as *const u8 as *const c_char, 0 as c_int);
     }
+}
+#[no_mangle]
+pub unsafe extern "C" fn spAnimationState_create(
+    mut data: *mut spAnimationStateData,
+) -> *mut spAnimationState {
+    let mut internal: *mut _spAnimationState = 0 as *mut _spAnimationState;
+    let mut self_0: *mut spAnimationState = 0 as *mut spAnimationState;
+    spAnimationState_initStatics();
     internal = _spCalloc(
         1 as c_int as size_t,
         ::core::mem::size_of::<_spAnimationState>() as c_ulong,
@@ -7162,11 +7162,7 @@ pub unsafe extern "C" fn _spAnimationState_disposeTrackEntries(
     }
 }
 #[no_mangle]
-pub unsafe extern "C" fn spAnimationState_create(
-    mut data: *mut spAnimationStateData,
-) -> *mut spAnimationState {
-    let mut internal: *mut _spAnimationState = std::ptr::null_mut();
-    let mut self_0: *mut spAnimationState = std::ptr::null_mut();
+pub unsafe extern "C" fn spAnimationState_initStatics() {
     if SP_EMPTY_ANIMATION.is_null() {
         SP_EMPTY_ANIMATION = 1 as c_int as *mut spAnimation;
         SP_EMPTY_ANIMATION = spAnimation_create(
@@ -7175,6 +7171,14 @@ pub unsafe extern "C" fn spAnimationState_create(
             0 as c_int as c_float,
         );
     }
+}
+#[no_mangle]
+pub unsafe extern "C" fn spAnimationState_create(
+    mut data: *mut spAnimationStateData,
+) -> *mut spAnimationState {
+    let mut internal: *mut _spAnimationState = std::ptr::null_mut();
+    let mut self_0: *mut spAnimationState = std::ptr::null_mut();
+    spAnimationState_initStatics();
     internal = _spCalloc(
         1 as c_int as size_t,
         ::core::mem::size_of::<_spAnimationState>() as c_ulong,
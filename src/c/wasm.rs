@@ -35,11 +35,10 @@
 #![allow(clippy::missing_const_for_fn)]
 
 use crate::c::FILE;
+use crate::memory::{self, MemoryCategory};
 use std::alloc::Layout;
 use std::any::Any;
-use std::collections::HashMap;
 use std::ffi::{CStr, CString};
-use std::sync::{Arc, Mutex, Once};
 
 pub mod types {
     #[allow(non_camel_case_types)]
@@ -77,68 +76,93 @@ use self::types::*;
 #[allow(non_camel_case_types)]
 type size_t = c_ulong;
 
-#[derive(Default)]
-struct Allocator {
-    allocations: HashMap<*const c_void, Layout>,
-}
+/// Allocates memory for the C runtime.
+///
+/// The size and [`MemoryCategory`] of each allocation are stored in a header just before the
+/// pointer handed to C, so freeing and reallocating need no lookup table, and the allocator needs
+/// no lock. Every allocation is also counted in [`crate::memory`], which updates a few atomic
+/// counters shared by all threads on each allocation and free, so threads working on separate
+/// skeletons still share those cache lines.
+struct Allocator;
+
+static ALLOCATOR: Allocator = Allocator;
 
 impl Allocator {
-    fn singleton() -> Arc<Mutex<Allocator>> {
-        static START: Once = Once::new();
-        static mut INSTANCE: Option<Arc<Mutex<Allocator>>> = None;
-        START.call_once(|| unsafe {
-            INSTANCE = Some(Arc::new(Mutex::new(Allocator::default())));
-        });
-        unsafe {
-            let singleton = INSTANCE.as_ref().unwrap();
-            singleton.clone()
-        }
+    /// Alignment of every allocation, matching what `malloc` guarantees for the types spine-c uses.
+    const ALIGN: usize = 8;
+
+    /// Size of the header holding the allocation size and category. A multiple of
+    /// [`Allocator::ALIGN`] so the pointer after it stays aligned.
+    const HEADER: usize = if std::mem::size_of::<usize>() * 2 > Self::ALIGN {
+        std::mem::size_of::<usize>() * 2
+    } else {
+        Self::ALIGN
+    };
+
+    fn layout(size: usize) -> Layout {
+        Layout::from_size_align(size.checked_add(Self::HEADER).unwrap(), Self::ALIGN).unwrap()
+    }
+
+    /// The start of the block (and header) for a pointer returned by [`Allocator::malloc`].
+    unsafe fn header(ptr: *const c_void) -> *mut usize {
+        ptr.cast::<u8>()
+            .cast_mut()
+            .sub(Self::HEADER)
+            .cast::<usize>()
     }
 
-    pub fn malloc(&mut self, size: usize) -> *mut c_void {
+    pub fn malloc(&self, size: usize) -> *mut c_void {
         if size > 0 {
-            let layout = std::alloc::Layout::array::<u8>(size)
-                .unwrap()
-                .align_to(8)
-                .unwrap();
-            let ptr = unsafe { std::alloc::alloc(layout) };
-            self.allocations.insert(ptr as *const c_void, layout);
-            ptr.cast::<c_void>()
+            unsafe {
+                let block = std::alloc::alloc(Self::layout(size));
+                if block.is_null() {
+                    std::alloc::handle_alloc_error(Self::layout(size));
+                }
+                let category = memory::record_alloc(size);
+                block.cast::<usize>().write(size);
+                block.cast::<usize>().add(1).write(category.index());
+                block.add(Self::HEADER).cast::<c_void>()
+            }
         } else {
             std::ptr::null_mut()
         }
     }
 
-    pub unsafe fn realloc(&mut self, ptr: *const c_void, size: usize) -> *mut c_void {
-        let new_memory = self.malloc(size);
-        if !new_memory.is_null() {
-            let layout = self.allocations.get(&ptr).unwrap();
-            spine_memcpy(new_memory, ptr, layout.size() as size_t);
+    pub unsafe fn realloc(&self, ptr: *const c_void, size: usize) -> *mut c_void {
+        if size == 0 {
+            self.free(ptr);
+            return std::ptr::null_mut();
+        }
+        let header = Self::header(ptr);
+        let old_size = header.read();
+        let category = MemoryCategory::from_index(header.add(1).read());
+        let block = std::alloc::realloc(
+            header.cast::<u8>(),
+            Self::layout(old_size),
+            size.checked_add(Self::HEADER).unwrap(),
+        );
+        if block.is_null() {
+            std::alloc::handle_alloc_error(Self::layout(size));
         }
-        self.free(ptr);
-        new_memory
+        block.cast::<usize>().write(size);
+        memory::record_free(old_size, category);
+        memory::record_alloc_in(size, category);
+        block.add(Self::HEADER).cast::<c_void>()
     }
 
     #[allow(dead_code)]
-    pub unsafe fn size(&mut self, ptr: *const c_void) -> usize {
-        self.allocations.get(&ptr).unwrap().size()
+    pub unsafe fn size(&self, ptr: *const c_void) -> usize {
+        Self::header(ptr).read()
     }
 
-    pub unsafe fn free(&mut self, ptr: *const c_void) {
+    pub unsafe fn free(&self, ptr: *const c_void) {
         if !ptr.is_null() {
-            let layout = self.allocations.remove(&ptr).unwrap();
-            unsafe { std::alloc::dealloc(ptr as *mut u8, layout) };
+            let header = Self::header(ptr);
+            let size = header.read();
+            memory::record_free(size, MemoryCategory::from_index(header.add(1).read()));
+            std::alloc::dealloc(header.cast::<u8>(), Self::layout(size));
         }
     }
-
-    #[allow(dead_code)]
-    pub fn size_allocated(&self) -> usize {
-        let mut size = 0;
-        for allocation in self.allocations.values() {
-            size += allocation.size();
-        }
-        size
-    }
 }
 
 #[no_mangle]
@@ -787,7 +811,7 @@ pub unsafe extern "C" fn spine_strrchr(mut p: *const c_char, ch: c_int) -> *mut
 
 #[no_mangle]
 unsafe extern "C" fn spine_rand() -> c_int {
-    unimplemented!();
+    crate::random::next()
 }
 
 #[no_mangle]
@@ -827,17 +851,13 @@ extern "C" fn spine_fmodf(x: c_float, y: c_float) -> c_float {
 
 #[no_mangle]
 unsafe extern "C" fn spine_malloc(size: size_t) -> *mut c_void {
-    let singleton = Allocator::singleton();
-    let mut allocator = singleton.lock().unwrap();
-    allocator.malloc(size as usize)
+    ALLOCATOR.malloc(size as usize)
 }
 
 #[no_mangle]
 unsafe extern "C" fn spine_realloc(ptr: *mut c_void, size: size_t) -> *mut c_void {
     if !ptr.is_null() {
-        let singleton = Allocator::singleton();
-        let mut allocator = singleton.lock().unwrap();
-        allocator.realloc(ptr, size as usize)
+        ALLOCATOR.realloc(ptr, size as usize)
     } else {
         std::ptr::null_mut()
     }
@@ -846,9 +866,7 @@ unsafe extern "C" fn spine_realloc(ptr: *mut c_void, size: size_t) -> *mut c_voi
 #[no_mangle]
 unsafe extern "C" fn spine_free(ptr: *mut c_void) {
     if !ptr.is_null() && ptr as usize != 1 {
-        let singleton = Allocator::singleton();
-        let mut allocator = singleton.lock().unwrap();
-        allocator.free(ptr);
+        ALLOCATOR.free(ptr);
     }
 }
 
@@ -1027,35 +1045,75 @@ macro_rules! spine_sscanf {
 mod tests {
     use std::ffi::CString;
 
-    use crate::c::{
-        c_uint,
-        wasm::{spine_strtol, spine_strtoul},
+    use crate::{
+        c::{
+            c_uint,
+            wasm::{spine_strtol, spine_strtoul},
+        },
+        memory,
     };
 
     use super::{spine_strlen, Allocator};
 
     #[test]
     fn allocator() {
-        let mut allocator = Allocator::default();
-        let mut allocations = vec![];
-        for _ in 0..30 {
-            let data = allocator.malloc(255).cast::<u8>();
-            unsafe {
-                for i in 0..255 {
-                    *data.offset(i) = i as u8;
-                }
-                for i in 0..255 {
-                    assert_eq!(*data.offset(i), i as u8);
+        let allocator = Allocator;
+        memory::assert_no_leaks(|| {
+            let mut allocations = vec![];
+            for _ in 0..30 {
+                let data = allocator.malloc(255).cast::<u8>();
+                unsafe {
+                    for i in 0..255 {
+                        *data.offset(i) = i as u8;
+                    }
+                    for i in 0..255 {
+                        assert_eq!(*data.offset(i), i as u8);
+                    }
+                    assert_eq!(allocator.size(data as *const super::c_void), 255);
                 }
-                assert_eq!(allocator.size(data as *const super::c_void), 255);
+                allocations.push(data);
             }
-            allocations.push(data);
-        }
-        assert_eq!(allocator.size_allocated(), 30 * 255);
-        for allocation in &allocations {
-            unsafe { allocator.free(*allocation as *const super::c_void) }
-        }
-        assert_eq!(allocator.size_allocated(), 0);
+            for allocation in &allocations {
+                unsafe { allocator.free(*allocation as *const super::c_void) }
+            }
+        });
+    }
+
+    #[test]
+    fn allocator_realloc() {
+        let allocator = Allocator;
+        memory::assert_no_leaks(|| unsafe {
+            let data = allocator.malloc(16).cast::<u8>();
+            for i in 0..16 {
+                *data.offset(i) = i as u8;
+            }
+            let data = allocator.realloc(data.cast(), 4096).cast::<u8>();
+            assert_eq!(allocator.size(data.cast()), 4096);
+            let data = allocator.realloc(data.cast(), 8).cast::<u8>();
+            assert_eq!(allocator.size(data.cast()), 8);
+            for i in 0..8 {
+                assert_eq!(*data.offset(i), i as u8);
+            }
+            assert!(allocator.realloc(data.cast(), 0).is_null());
+        });
+    }
+
+    #[test]
+    fn allocator_threads() {
+        let allocator = Allocator;
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    memory::assert_no_leaks(|| {
+                        for size in 1..500 {
+                            let data = allocator.malloc(size);
+                            assert_eq!(data as usize % Allocator::ALIGN, 0);
+                            unsafe { allocator.free(data) };
+                        }
+                    });
+                });
+            }
+        });
     }
 
     #[test]
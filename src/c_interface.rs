@@ -7,6 +7,7 @@ use std::{
     hash::Hash,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 /// Create a type from its underlying [`spine-c`](`crate::c`) pointer type.
@@ -328,6 +329,24 @@ where
     }
 }
 
+/// A new unique id for an object which handles are checked against, such as a skeleton.
+///
+/// Handles store the id of their parent alongside its pointer, so a handle kept after its parent
+/// is dropped does not resolve against a new parent allocated at the same address. An id of `0`
+/// is unknown, which is used by wrappers that do not own their object and whose owner is not
+/// known, such as the atlas of an [`AtlasPage`](`crate::AtlasPage`). An unknown id never
+/// matches, so handles to or from such wrappers never resolve.
+pub(crate) fn new_handle_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Whether the parent id stored in a handle matches the id of a parent at the same address, see
+/// [`new_handle_id`].
+pub(crate) const fn handle_ids_match(handle_parent_id: u64, parent_id: u64) -> bool {
+    handle_parent_id != 0 && handle_parent_id == parent_id
+}
+
 /// A wrapper over a raw pointer with the [`Send`] and [`Sync`] traits.
 #[derive(Clone, Copy)]
 pub struct SyncPtr<T>(pub *mut T);
@@ -998,24 +1017,34 @@ macro_rules! c_handle_decl {
         pub struct $name {
             c_item: crate::c_interface::SyncPtr<$c_type>,
             c_parent: crate::c_interface::SyncPtr<$c_parent>,
+            parent_id: u64,
         }
 
         impl $name {
             #[must_use]
-            pub(crate) const fn new(c_item: *const $c_type, c_parent: *const $c_parent) -> Self {
+            pub(crate) const fn new(
+                c_item: *const $c_type,
+                c_parent: *const $c_parent,
+                parent_id: u64,
+            ) -> Self {
                 Self {
                     c_item: SyncPtr(c_item as *mut $c_type),
                     c_parent: SyncPtr(c_parent as *mut $c_parent),
+                    parent_id,
                 }
             }
 
-            /// Safely acquired the item, verifying its existence using its parent.
+            /// Safely acquired the item, verifying its existence using its parent. Returns [`None`]
+            /// if `parent` is not the object this handle was created from, even if it was
+            /// allocated at the same address after that object was dropped.
             #[must_use]
             pub fn get<'a>(
                 &self,
                 parent: &'a $parent,
             ) -> Option<crate::c_interface::CTmpRef<'a, $parent, $type>> {
-                if parent.c_ptr() == self.c_parent.0 {
+                if parent.c_ptr() == self.c_parent.0
+                    && crate::c_interface::handle_ids_match(self.parent_id, parent.handle_id())
+                {
                     Some(crate::c_interface::CTmpRef::new(parent, unsafe {
                         <$type>::new_from_ptr(self.c_item.0)
                     }))
@@ -1024,13 +1053,17 @@ macro_rules! c_handle_decl {
                 }
             }
 
-            /// Safely acquired the item, verifying its existence using its parent.
+            /// Safely acquired the item, verifying its existence using its parent. Returns [`None`]
+            /// if `parent` is not the object this handle was created from, even if it was
+            /// allocated at the same address after that object was dropped.
             #[must_use]
             pub fn get_mut<'a>(
                 &self,
                 parent: &'a mut $parent,
             ) -> Option<crate::c_interface::CTmpMut<'a, $parent, $type>> {
-                if parent.c_ptr() == self.c_parent.0 {
+                if parent.c_ptr() == self.c_parent.0
+                    && crate::c_interface::handle_ids_match(self.parent_id, parent.handle_id())
+                {
                     Some(crate::c_interface::CTmpMut::new(parent, unsafe {
                         <$type>::new_from_ptr(self.c_item.0)
                     }))
@@ -1059,6 +1092,8 @@ macro_rules! c_handle_indexed_decl {
             index: i32,
             c_item: crate::c_interface::SyncPtr<$c_type>,
             c_parent: crate::c_interface::SyncPtr<$c_parent>,
+            parent_id: u64,
+            item_id: u64,
         }
 
         impl $name {
@@ -1067,21 +1102,29 @@ macro_rules! c_handle_indexed_decl {
                 index: i32,
                 c_item: *const $c_type,
                 c_parent: *const $c_parent,
+                parent_id: u64,
+                item_id: u64,
             ) -> Self {
                 Self {
                     index,
                     c_item: SyncPtr(c_item as *mut $c_type),
                     c_parent: SyncPtr(c_parent as *mut $c_parent),
+                    parent_id,
+                    item_id,
                 }
             }
 
-            /// Safely acquired the item, verifying its existence using its parent.
+            /// Safely acquired the item, verifying its existence using its parent. Returns [`None`]
+            /// if `parent` is not the object this handle was created from, even if it was
+            /// allocated at the same address after that object was dropped.
             #[must_use]
             pub fn get<'a>(
                 &self,
                 parent: &'a $parent,
             ) -> Option<crate::c_interface::CTmpRef<'a, $parent, $type>> {
-                if parent.c_ptr() == self.c_parent.0 {
+                if parent.c_ptr() == self.c_parent.0
+                    && crate::c_interface::handle_ids_match(self.parent_id, parent.handle_id())
+                {
                     if <$type>::handle_valid(self) {
                         Some(crate::c_interface::CTmpRef::new(parent, unsafe {
                             <$type>::new_from_ptr(self.c_item.0)
@@ -1094,13 +1137,17 @@ macro_rules! c_handle_indexed_decl {
                 }
             }
 
-            /// Safely acquired the item, verifying its existence using its parent.
+            /// Safely acquired the item, verifying its existence using its parent. Returns [`None`]
+            /// if `parent` is not the object this handle was created from, even if it was
+            /// allocated at the same address after that object was dropped.
             #[must_use]
             pub fn get_mut<'a>(
                 &self,
                 parent: &'a mut $parent,
             ) -> Option<crate::c_interface::CTmpMut<'a, $parent, $type>> {
-                if parent.c_ptr() == self.c_parent.0 {
+                if parent.c_ptr() == self.c_parent.0
+                    && crate::c_interface::handle_ids_match(self.parent_id, parent.handle_id())
+                {
                     if <$type>::handle_valid(self) {
                         Some(crate::c_interface::CTmpMut::new(parent, unsafe {
                             <$type>::new_from_ptr(self.c_item.0)
@@ -4,6 +4,7 @@ use crate::c::c_float;
 
 /// RGBA F32 color that is byte-compatible with the Spine runtime.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Color {
     pub r: c_float,
@@ -75,24 +75,40 @@ pub struct SkeletonController {
     pub animation_state: AnimationState,
     pub clipper: SkeletonClipping,
     pub settings: SkeletonControllerSettings,
+    accumulator: f64,
+    tick_count: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SkeletonControllerSettings {
     /// Set to `true` if the textures are expected to have premultiplied alpha.
     pub premultiplied_alpha: bool,
+    /// Set to `true` to read the premultiplied alpha setting of each attachment from its atlas
+    /// page instead, so skeletons using both kinds of pages are drawn correctly. Attachments
+    /// without an atlas page, and Spine 3.8 atlases, use
+    /// [`premultiplied_alpha`](`SkeletonControllerSettings::premultiplied_alpha`).
+    pub premultiplied_alpha_from_atlas: bool,
+    /// Skip attachments whose atlas page does not have a texture yet, see
+    /// [`Atlas::new_deferred`](`crate::Atlas::new_deferred`).
+    pub skip_pending_textures: bool,
     /// The cull direction to use for the vertices.
     pub cull_direction: CullDirection,
     /// The color space to use for the colors returned in [`SkeletonRenderable`] or  [`SkeletonCombinedRenderable`].
     pub color_space: ColorSpace,
+    /// How [`SkeletonController::update`] advances time.
+    pub update_mode: UpdateMode,
 }
 
 impl Default for SkeletonControllerSettings {
     fn default() -> Self {
         Self {
             premultiplied_alpha: false,
+            premultiplied_alpha_from_atlas: false,
+            skip_pending_textures: false,
             cull_direction: CullDirection::Clockwise,
             color_space: ColorSpace::SRGB,
+            update_mode: UpdateMode::Variable,
         }
     }
 }
@@ -111,6 +127,25 @@ impl SkeletonControllerSettings {
         }
     }
 
+    #[must_use]
+    pub const fn with_premultiplied_alpha_from_atlas(
+        self,
+        premultiplied_alpha_from_atlas: bool,
+    ) -> Self {
+        Self {
+            premultiplied_alpha_from_atlas,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn with_skip_pending_textures(self, skip_pending_textures: bool) -> Self {
+        Self {
+            skip_pending_textures,
+            ..self
+        }
+    }
+
     #[must_use]
     pub const fn with_cull_direction(self, cull_direction: CullDirection) -> Self {
         Self {
@@ -126,6 +161,45 @@ impl SkeletonControllerSettings {
             ..self
         }
     }
+
+    /// Set how [`SkeletonController::update`] advances time, see [`UpdateMode`].
+    #[must_use]
+    pub const fn with_update_mode(self, update_mode: UpdateMode) -> Self {
+        Self {
+            update_mode,
+            ..self
+        }
+    }
+}
+
+/// The time stepping strategy used by [`SkeletonController::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UpdateMode {
+    /// Every call to [`SkeletonController::update`] advances the skeleton by exactly the delta it
+    /// was given.
+    Variable,
+    /// Deltas passed to [`SkeletonController::update`] are accumulated and the skeleton is advanced
+    /// in steps of exactly `1 / hz` seconds, at most `max_substeps` times per update. Any time
+    /// exceeding `max_substeps` is discarded to prevent a slow frame from stalling the next. A
+    /// `max_substeps` of 0 is treated as 1, so the skeleton always advances.
+    ///
+    /// Because every step uses the same delta, two controllers which have taken the same number
+    /// of steps (see [`SkeletonController::tick_count`]) with the same animation calls in between
+    /// have identical poses, regardless of the frame rate driving them. This is useful for
+    /// lockstep networking. A `hz` of 0 behaves like [`UpdateMode::Variable`].
+    Fixed { hz: u32, max_substeps: u32 },
+}
+
+impl UpdateMode {
+    /// The length of one fixed step in seconds, or [`None`] if this mode does not use fixed steps.
+    #[must_use]
+    pub fn step_seconds(&self) -> Option<f32> {
+        match *self {
+            Self::Fixed { hz, .. } if hz > 0 => Some(1. / hz as f32),
+            _ => None,
+        }
+    }
 }
 
 impl SkeletonController {
@@ -143,6 +217,8 @@ impl SkeletonController {
             animation_state: AnimationState::new(animation_state_data),
             clipper: SkeletonClipping::new(),
             settings: SkeletonControllerSettings::default(),
+            accumulator: 0.,
+            tick_count: 0,
         }
     }
 
@@ -152,10 +228,71 @@ impl SkeletonController {
     }
 
     /// Updates the animation state, applies to the skeleton, and updates world transforms.
+    ///
+    /// If the settings use [`UpdateMode::Fixed`], the delta is accumulated and the skeleton is
+    /// only advanced in whole fixed steps, so it may not change at all during a short frame. See
+    /// [`SkeletonController::interpolation_factor`] for smoothing the remainder when rendering.
     pub fn update(&mut self, delta_seconds: f32) {
+        match self.settings.update_mode {
+            UpdateMode::Fixed { max_substeps, .. } => {
+                let Some(step) = self.settings.update_mode.step_seconds() else {
+                    self.advance(delta_seconds);
+                    return;
+                };
+                let max_substeps = max_substeps.max(1);
+                self.accumulator += f64::from(delta_seconds);
+                let mut substeps = 0;
+                while self.accumulator >= f64::from(step) && substeps < max_substeps {
+                    self.advance(step);
+                    self.accumulator -= f64::from(step);
+                    substeps += 1;
+                }
+                if self.accumulator >= f64::from(step) {
+                    self.accumulator %= f64::from(step);
+                }
+            }
+            UpdateMode::Variable => {
+                self.advance(delta_seconds);
+            }
+        }
+    }
+
+    /// Advances the skeleton by exactly one fixed step, ignoring any accumulated time.
+    ///
+    /// Intended for lockstep simulations which drive ticks explicitly instead of passing frame
+    /// deltas to [`SkeletonController::update`]. If the settings do not use
+    /// [`UpdateMode::Fixed`], this does nothing.
+    pub fn fixed_step(&mut self) {
+        if let Some(step) = self.settings.update_mode.step_seconds() {
+            self.advance(step);
+        }
+    }
+
+    /// The fraction (0 to 1) of a fixed step that has accumulated but not yet been applied.
+    ///
+    /// Renderers can use this to interpolate between the previous and current poses. Always 0
+    /// when not using [`UpdateMode::Fixed`].
+    #[must_use]
+    pub fn interpolation_factor(&self) -> f32 {
+        self.settings
+            .update_mode
+            .step_seconds()
+            .map_or(0., |step| (self.accumulator / f64::from(step)) as f32)
+    }
+
+    /// The number of times the skeleton has been advanced since this controller was created.
+    ///
+    /// With [`UpdateMode::Fixed`], this is the number of fixed steps taken.
+    #[must_use]
+    pub const fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    fn advance(&mut self, delta_seconds: f32) {
         self.animation_state.update(delta_seconds);
         self.animation_state.apply(&mut self.skeleton);
         self.skeleton.update_world_transform();
+        self.tick_count += 1;
     }
 
     /// Render the skeleton using the [`SimpleDrawer`] and returns renderable mesh information.
@@ -166,6 +303,8 @@ impl SkeletonController {
         let renderables = SimpleDrawer {
             cull_direction: self.settings.cull_direction,
             premultiplied_alpha: self.settings.premultiplied_alpha,
+            premultiplied_alpha_from_atlas: self.settings.premultiplied_alpha_from_atlas,
+            skip_pending_textures: self.settings.skip_pending_textures,
             color_space: self.settings.color_space,
         }
         .draw(&mut self.skeleton, Some(&mut self.clipper));
@@ -179,7 +318,7 @@ impl SkeletonController {
                 color: renderable.color,
                 dark_color: renderable.dark_color,
                 blend_mode: renderable.blend_mode,
-                premultiplied_alpha: self.settings.premultiplied_alpha,
+                premultiplied_alpha: renderable.premultiplied_alpha,
                 attachment_renderer_object: renderable.attachment_renderer_object,
             })
             .collect()
@@ -190,6 +329,8 @@ impl SkeletonController {
         let renderables = CombinedDrawer {
             cull_direction: self.settings.cull_direction,
             premultiplied_alpha: self.settings.premultiplied_alpha,
+            premultiplied_alpha_from_atlas: self.settings.premultiplied_alpha_from_atlas,
+            skip_pending_textures: self.settings.skip_pending_textures,
             color_space: self.settings.color_space,
         }
         .draw(&mut self.skeleton, Some(&mut self.clipper));
@@ -202,7 +343,7 @@ impl SkeletonController {
                 colors: renderable.colors,
                 dark_colors: renderable.dark_colors,
                 blend_mode: renderable.blend_mode,
-                premultiplied_alpha: self.settings.premultiplied_alpha,
+                premultiplied_alpha: renderable.premultiplied_alpha,
                 attachment_renderer_object: renderable.attachment_renderer_object,
             })
             .collect()
@@ -233,3 +374,268 @@ pub struct SkeletonCombinedRenderable {
     pub premultiplied_alpha: bool,
     pub attachment_renderer_object: Option<*const c_void>,
 }
+
+/// A collection of [`SkeletonController`]s which are updated and drawn together, such as a crowd
+/// of characters. With the `rayon` feature, the controllers are updated and drawn in parallel on
+/// the rayon thread pool.
+///
+/// Results are always returned in the order the controllers were added, one entry per controller.
+///
+/// ```
+/// # #[path="./test.rs"]
+/// # mod test;
+/// use rusty_spine::controller::{SkeletonController, SkeletonControllerSet};
+///
+/// # let (skeleton_data, animation_state_data) = test::TestAsset::spineboy().instance_data();
+/// let mut crowd = SkeletonControllerSet::new();
+/// for _ in 0..100 {
+///     crowd.push(SkeletonController::new(
+///         skeleton_data.clone(),
+///         animation_state_data.clone(),
+///     ));
+/// }
+///
+/// // Every frame:
+/// for (index, renderables) in crowd.update_combined_renderables(0.016).iter().enumerate() {
+///     // Draw the renderables of controller `index`.
+/// }
+/// ```
+///
+/// Renderer objects in the returned renderables are passed back to the calling thread untouched,
+/// so textures can be used there as usual. See [Threading](`crate#threading`).
+#[derive(Debug, Default)]
+pub struct SkeletonControllerSet {
+    controllers: Vec<SkeletonController>,
+}
+
+impl SkeletonControllerSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a controller, returning its index.
+    pub fn push(&mut self, controller: SkeletonController) -> usize {
+        self.controllers.push(controller);
+        self.controllers.len() - 1
+    }
+
+    /// Remove the controller at `index`, shifting the indices of the controllers after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> SkeletonController {
+        self.controllers.remove(index)
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    #[must_use]
+    pub fn controllers(&self) -> &[SkeletonController] {
+        &self.controllers
+    }
+
+    pub fn controllers_mut(&mut self) -> &mut [SkeletonController] {
+        &mut self.controllers
+    }
+
+    #[must_use]
+    pub fn into_controllers(self) -> Vec<SkeletonController> {
+        self.controllers
+    }
+
+    /// Update every controller, see [`SkeletonController::update`].
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.map(|controller| controller.update(delta_seconds));
+    }
+
+    /// Render every controller, see [`SkeletonController::renderables`].
+    pub fn renderables(&mut self) -> Vec<Vec<SkeletonRenderable>> {
+        self.map(SkeletonController::renderables)
+    }
+
+    /// Render every controller, see [`SkeletonController::combined_renderables`].
+    pub fn combined_renderables(&mut self) -> Vec<Vec<SkeletonCombinedRenderable>> {
+        self.map(SkeletonController::combined_renderables)
+    }
+
+    /// Update and render every controller in a single pass, which is faster than calling
+    /// [`SkeletonControllerSet::update`] and [`SkeletonControllerSet::combined_renderables`]
+    /// separately.
+    pub fn update_combined_renderables(
+        &mut self,
+        delta_seconds: f32,
+    ) -> Vec<Vec<SkeletonCombinedRenderable>> {
+        self.map(|controller| {
+            controller.update(delta_seconds);
+            controller.combined_renderables()
+        })
+    }
+
+    #[cfg(feature = "rayon")]
+    fn map<T, F>(&mut self, f: F) -> Vec<T>
+    where
+        F: Fn(&mut SkeletonController) -> T + Sync,
+    {
+        use rayon::prelude::*;
+
+        /// Renderables hold renderer objects, which are raw pointers and not [`Send`]. They are
+        /// never dereferenced here, only moved back to the calling thread.
+        struct Unsync<T>(T);
+        unsafe impl<T> Send for Unsync<T> {}
+
+        let results: Vec<Unsync<T>> = self
+            .controllers
+            .par_iter_mut()
+            .map(|controller| Unsync(f(controller)))
+            .collect();
+        results.into_iter().map(|result| result.0).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn map<T, F>(&mut self, f: F) -> Vec<T>
+    where
+        F: Fn(&mut SkeletonController) -> T + Sync,
+    {
+        self.controllers.iter_mut().map(f).collect()
+    }
+}
+
+impl From<Vec<SkeletonController>> for SkeletonControllerSet {
+    fn from(controllers: Vec<SkeletonController>) -> Self {
+        Self { controllers }
+    }
+}
+
+impl FromIterator<SkeletonController> for SkeletonControllerSet {
+    fn from_iter<I: IntoIterator<Item = SkeletonController>>(iter: I) -> Self {
+        Self {
+            controllers: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::TestAsset;
+
+    use super::*;
+
+    fn fixed_controller() -> SkeletonController {
+        let (skeleton_data, animation_state_data) = TestAsset::spineboy().instance_data();
+        let mut controller = SkeletonController::new(skeleton_data, animation_state_data)
+            .with_settings(
+                SkeletonControllerSettings::new().with_update_mode(UpdateMode::Fixed {
+                    hz: 60,
+                    max_substeps: 8,
+                }),
+            );
+        let _ = controller
+            .animation_state
+            .set_animation_by_name(0, "run", true);
+        controller
+    }
+
+    /// Poses must only depend on the number of fixed steps, not the frame deltas.
+    #[test]
+    fn fixed_update_deterministic() {
+        let mut variable_frames = fixed_controller();
+        for i in 0..200 {
+            variable_frames.update(if i % 3 == 0 { 0.031 } else { 0.007 });
+        }
+        assert!(variable_frames.tick_count() > 0);
+        assert!((0. ..1.).contains(&variable_frames.interpolation_factor()));
+
+        let mut lockstep = fixed_controller();
+        for _ in 0..variable_frames.tick_count() {
+            lockstep.fixed_step();
+        }
+        assert_eq!(lockstep.tick_count(), variable_frames.tick_count());
+        for (a, b) in variable_frames
+            .skeleton
+            .bones()
+            .zip(lockstep.skeleton.bones())
+        {
+            assert_eq!(a.world_x().to_bits(), b.world_x().to_bits());
+            assert_eq!(a.world_y().to_bits(), b.world_y().to_bits());
+            assert_eq!(a.a().to_bits(), b.a().to_bits());
+            assert_eq!(a.d().to_bits(), b.d().to_bits());
+        }
+    }
+
+    #[test]
+    fn fixed_update_max_substeps() {
+        let mut controller = fixed_controller();
+        controller.update(10.);
+        assert_eq!(controller.tick_count(), 8);
+        assert!(controller.interpolation_factor() < 1.);
+
+        controller.settings.update_mode = UpdateMode::Fixed {
+            hz: 60,
+            max_substeps: 0,
+        };
+        controller.update(10.);
+        assert_eq!(controller.tick_count(), 9);
+    }
+
+    /// The set must give the same results, in the same order, as updating each controller alone.
+    #[test]
+    fn controller_set_matches_controllers() {
+        let animations = ["run", "walk", "jump", "idle"];
+        let controller = |animation: &str| {
+            let (skeleton_data, animation_state_data) = TestAsset::spineboy().instance_data();
+            let mut controller = SkeletonController::new(skeleton_data, animation_state_data);
+            controller
+                .animation_state
+                .set_animation_by_name(0, animation, true)
+                .unwrap();
+            controller
+        };
+        let mut set = animations
+            .iter()
+            .map(|animation| controller(animation))
+            .collect::<SkeletonControllerSet>();
+        let mut controllers = animations
+            .iter()
+            .map(|animation| controller(animation))
+            .collect::<Vec<_>>();
+        for _ in 0..10 {
+            let renderables = set.update_combined_renderables(0.1);
+            assert_eq!(renderables.len(), controllers.len());
+            for (controller, renderables) in controllers.iter_mut().zip(renderables) {
+                controller.update(0.1);
+                let expected = controller.combined_renderables();
+                assert_eq!(renderables.len(), expected.len());
+                for (renderable, expected) in renderables.iter().zip(expected.iter()) {
+                    assert_eq!(renderable.vertices, expected.vertices);
+                    assert_eq!(
+                        renderable.attachment_renderer_object,
+                        expected.attachment_renderer_object
+                    );
+                }
+            }
+        }
+        set.update(0.1);
+        assert_eq!(set.renderables().len(), 4);
+        let removed = set.remove(1);
+        assert_eq!(
+            removed
+                .animation_state
+                .get_current(0)
+                .unwrap()
+                .animation()
+                .name(),
+            "walk"
+        );
+        assert_eq!(set.len(), 3);
+    }
+}
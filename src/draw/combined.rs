@@ -20,11 +20,67 @@ pub struct CombinedRenderable {
     pub indices: Vec<u16>,
     /// The blend mode to use when drawing this mesh.
     pub blend_mode: BlendMode,
+    /// Whether the colors of this mesh are premultiplied, and its texture is expected to have
+    /// premultiplied alpha.
+    pub premultiplied_alpha: bool,
     /// The attachment's renderer object as a raw pointer. Usually represents the texture created
     /// from [`extension::set_create_texture_cb`].
     pub attachment_renderer_object: Option<*const c_void>,
 }
 
+/// An owned copy of a [`CombinedRenderable`] without the attachment's renderer object pointer.
+///
+/// Useful for storing or sending draw data somewhere the renderer object is meaningless, such as
+/// another process. If the `serde` feature is enabled, this type can be serialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CombinedRenderableData {
+    /// A list of vertex attributes for a mesh.
+    pub vertices: Vec<[f32; 2]>,
+    /// A list of UV attributes for a mesh.
+    pub uvs: Vec<[f32; 2]>,
+    /// A list of color attributes for a mesh.
+    pub colors: Vec<[f32; 4]>,
+    /// A list of dark color attributes for a mesh.
+    /// See the [Spine User Guide](http://en.esotericsoftware.com/spine-slots#Tint-black).
+    pub dark_colors: Vec<[f32; 4]>,
+    /// A list of indices for a mesh.
+    pub indices: Vec<u16>,
+    /// The blend mode to use when drawing this mesh.
+    pub blend_mode: BlendMode,
+    /// Whether the colors of this mesh are premultiplied, and its texture is expected to have
+    /// premultiplied alpha.
+    pub premultiplied_alpha: bool,
+}
+
+impl From<CombinedRenderable> for CombinedRenderableData {
+    fn from(renderable: CombinedRenderable) -> Self {
+        Self {
+            vertices: renderable.vertices,
+            uvs: renderable.uvs,
+            colors: renderable.colors,
+            dark_colors: renderable.dark_colors,
+            indices: renderable.indices,
+            blend_mode: renderable.blend_mode,
+            premultiplied_alpha: renderable.premultiplied_alpha,
+        }
+    }
+}
+
+impl From<&CombinedRenderable> for CombinedRenderableData {
+    fn from(renderable: &CombinedRenderable) -> Self {
+        Self {
+            vertices: renderable.vertices.clone(),
+            uvs: renderable.uvs.clone(),
+            colors: renderable.colors.clone(),
+            dark_colors: renderable.dark_colors.clone(),
+            indices: renderable.indices.clone(),
+            blend_mode: renderable.blend_mode,
+            premultiplied_alpha: renderable.premultiplied_alpha,
+        }
+    }
+}
+
 /// A combined drawer with a mesh combining optimization.
 ///
 /// Assumes use of the default atlas attachment loader.
@@ -33,14 +89,22 @@ pub struct CombinedRenderable {
 pub struct CombinedDrawer {
     pub cull_direction: CullDirection,
     pub premultiplied_alpha: bool,
+    /// Set to `true` to read the premultiplied alpha setting of each attachment from its atlas
+    /// page instead, using [`CombinedDrawer::premultiplied_alpha`] for attachments without one.
+    /// Spine 3.8 atlases do not store this setting.
+    pub premultiplied_alpha_from_atlas: bool,
+    /// Set to `true` to skip attachments whose atlas page does not have a texture yet, see
+    /// [`Atlas::new_deferred`](`crate::Atlas::new_deferred`). Otherwise these attachments are
+    /// drawn with no renderer object.
+    pub skip_pending_textures: bool,
     pub color_space: ColorSpace,
 }
 
 impl CombinedDrawer {
     /// This function returns a list of [`CombinedRenderable`] structs containing all the necessary
     /// data to create and render meshes. Attachments are batched together into a single renderable
-    /// so long as their blend mode or renderer object is not different from the previous
-    /// attachment. If a [`SkeletonClipping`] is provided, meshes will be properly clipped. The
+    /// so long as their blend mode, premultiplied alpha setting, or renderer object is not
+    /// different from the previous attachment. If a [`SkeletonClipping`] is provided, meshes will be properly clipped. The
     /// renderables are expected to be rendered in the order provided with the first renderable
     /// being drawn behind all the others.
     ///
@@ -62,6 +126,7 @@ impl CombinedDrawer {
         let mut dark_colors = vec![];
         let mut indices = vec![];
         let mut blend_mode = BlendMode::Normal;
+        let mut premultiplied_alpha = self.premultiplied_alpha;
         let mut attachment_renderer_object = None;
         let mut world_vertices = vec![];
         world_vertices.resize(1000, 0.);
@@ -78,21 +143,19 @@ impl CombinedDrawer {
                 continue;
             }
 
-            if let Some(clipping_attachment) = slot.attachment().and_then(|a| a.as_clipping()) {
+            if let Some(clipping_attachment) = slot.clipping_attachment() {
                 if let Some(clipper) = clipper.as_deref_mut() {
                     clipper.clip_start(&slot, &clipping_attachment);
                 }
                 continue;
-            } else if slot.attachment().and_then(|a| a.as_mesh()).is_none()
-                && slot.attachment().and_then(|a| a.as_region()).is_none()
-            {
+            } else if slot.mesh_attachment().is_none() && slot.region_attachment().is_none() {
                 if let Some(clipper) = clipper.as_deref_mut() {
                     clipper.clip_end(&slot);
                 }
                 continue;
             }
 
-            if let Some(mesh_attachment) = slot.attachment().and_then(|a| a.as_mesh()) {
+            if let Some(mesh_attachment) = slot.mesh_attachment() {
                 unsafe {
                     mesh_attachment.compute_world_vertices(
                         &slot,
@@ -103,7 +166,7 @@ impl CombinedDrawer {
                         2,
                     );
                 }
-            } else if let Some(region_attachment) = slot.attachment().and_then(|a| a.as_region()) {
+            } else if let Some(region_attachment) = slot.region_attachment() {
                 #[cfg(feature = "spine38")]
                 unsafe {
                     region_attachment.compute_world_vertices(
@@ -119,57 +182,70 @@ impl CombinedDrawer {
                 }
             }
 
+            if self.skip_pending_textures && super::texture_pending(&slot) {
+                if let Some(clipper) = clipper.as_deref_mut() {
+                    clipper.clip_end(&slot);
+                }
+                continue;
+            }
+
             let next_blend_mode = slot.data().blend_mode();
-            let next_attachment_renderer_object =
-                slot.attachment().and_then(|a| a.as_mesh()).map_or_else(
-                    || {
-                        slot.attachment().and_then(|a| a.as_region()).map_or_else(
-                            || {
-                                unreachable!();
-                            },
-                            |region_attachment| {
-                                let next_attachment_renderer_object = unsafe {
-                                    region_attachment
-                                        .renderer_object()
-                                        .get_atlas_region()
-                                        .unwrap()
-                                        .page()
-                                        .c_ptr_ref()
-                                        .rendererObject
-                                        .cast_const()
-                                };
-                                if next_attachment_renderer_object.is_null() {
-                                    None
-                                } else {
-                                    Some(next_attachment_renderer_object)
-                                }
-                            },
-                        )
-                    },
-                    |mesh_attachment| {
-                        let next_attachment_renderer_object = unsafe {
-                            mesh_attachment
-                                .renderer_object()
-                                .get_atlas_region()
-                                .unwrap()
-                                .page()
-                                .c_ptr_ref()
-                                .rendererObject
-                                .cast_const()
-                        };
-                        if next_attachment_renderer_object.is_null() {
-                            None
-                        } else {
-                            Some(next_attachment_renderer_object)
-                        }
-                    },
-                );
+            let next_premultiplied_alpha = super::premultiplied_alpha(
+                &slot,
+                self.premultiplied_alpha_from_atlas,
+                self.premultiplied_alpha,
+            );
+            let next_attachment_renderer_object = slot.mesh_attachment().map_or_else(
+                || {
+                    slot.region_attachment().map_or_else(
+                        || {
+                            unreachable!();
+                        },
+                        |region_attachment| {
+                            let next_attachment_renderer_object = unsafe {
+                                region_attachment
+                                    .renderer_object()
+                                    .get_atlas_region()
+                                    .unwrap()
+                                    .page()
+                                    .c_ptr_ref()
+                                    .rendererObject
+                                    .cast_const()
+                            };
+                            if next_attachment_renderer_object.is_null() {
+                                None
+                            } else {
+                                Some(next_attachment_renderer_object)
+                            }
+                        },
+                    )
+                },
+                |mesh_attachment| {
+                    let next_attachment_renderer_object = unsafe {
+                        mesh_attachment
+                            .renderer_object()
+                            .get_atlas_region()
+                            .unwrap()
+                            .page()
+                            .c_ptr_ref()
+                            .rendererObject
+                            .cast_const()
+                    };
+                    if next_attachment_renderer_object.is_null() {
+                        None
+                    } else {
+                        Some(next_attachment_renderer_object)
+                    }
+                },
+            );
 
-            if slot_index == 0 {
+            if indices.is_empty() {
                 blend_mode = next_blend_mode;
+                premultiplied_alpha = next_premultiplied_alpha;
                 attachment_renderer_object = next_attachment_renderer_object;
             }
             if blend_mode != next_blend_mode
+                || premultiplied_alpha != next_premultiplied_alpha
                 || attachment_renderer_object != next_attachment_renderer_object
             {
                 renderables.push(CombinedRenderable {
@@ -179,6 +255,7 @@ impl CombinedDrawer {
                     colors,
                     dark_colors,
                     blend_mode,
+                    premultiplied_alpha,
                     attachment_renderer_object,
                 });
                 vertices = vec![];
@@ -190,13 +267,12 @@ impl CombinedDrawer {
                 index_base = 0;
             }
             blend_mode = next_blend_mode;
+            premultiplied_alpha = next_premultiplied_alpha;
             attachment_renderer_object = next_attachment_renderer_object;
 
-            let (color, dark_color) = if let Some(mesh_attachment) =
-                slot.attachment().and_then(|a| a.as_mesh())
-            {
+            let (color, dark_color) = if let Some(mesh_attachment) = slot.mesh_attachment() {
                 let mut color = mesh_attachment.color() * slot.color() * skeleton.color();
-                if self.premultiplied_alpha {
+                if premultiplied_alpha {
                     color.premultiply_alpha();
                 }
                 color = match self.color_space {
@@ -207,7 +283,7 @@ impl CombinedDrawer {
                 let mut dark_color = slot
                     .dark_color()
                     .unwrap_or_else(|| Color::new_rgba(0.0, 0.0, 0.0, 0.0));
-                if self.premultiplied_alpha {
+                if premultiplied_alpha {
                     dark_color.a = 1.0;
                     dark_color.premultiply_alpha();
                 }
@@ -281,9 +357,9 @@ impl CombinedDrawer {
                 }
 
                 (color, dark_color)
-            } else if let Some(region_attachment) = slot.attachment().and_then(|a| a.as_region()) {
+            } else if let Some(region_attachment) = slot.region_attachment() {
                 let mut color = region_attachment.color() * slot.color() * skeleton.color();
-                if self.premultiplied_alpha {
+                if premultiplied_alpha {
                     color.premultiply_alpha();
                 }
                 color = match self.color_space {
@@ -294,7 +370,7 @@ impl CombinedDrawer {
                 let mut dark_color = slot
                     .dark_color()
                     .unwrap_or_else(|| Color::new_rgba(0.0, 0.0, 0.0, 0.0));
-                if self.premultiplied_alpha {
+                if premultiplied_alpha {
                     dark_color.a = 1.0;
                     dark_color.premultiply_alpha();
                 } else {
@@ -410,6 +486,7 @@ impl CombinedDrawer {
                 colors,
                 dark_colors,
                 blend_mode,
+                premultiplied_alpha,
                 attachment_renderer_object,
             });
         }
@@ -435,6 +512,8 @@ mod test {
             let drawer = CombinedDrawer {
                 cull_direction: CullDirection::Clockwise,
                 premultiplied_alpha: false,
+                premultiplied_alpha_from_atlas: false,
+                skip_pending_textures: false,
                 color_space: ColorSpace::Linear,
             };
             let mut clipper = SkeletonClipping::new();
@@ -442,4 +521,228 @@ mod test {
             assert!(!renderables.is_empty());
         }
     }
+
+    /// Attachments on pages with different premultiplied alpha settings must not be batched.
+    #[cfg(not(feature = "spine38"))]
+    #[test]
+    fn combined_drawer_premultiplied_alpha_from_atlas() {
+        use std::sync::Arc;
+
+        use crate::{
+            atlas::{AtlasBuilder, AtlasPageDesc},
+            Atlas, SkeletonJson,
+        };
+
+        // Split spineboy's regions alternately between a straight and a premultiplied page.
+        let asset = TestAsset::spineboy();
+        let page = AtlasBuilder::from_atlas(&asset.atlas()).pages.remove(0);
+        let mut pages = [
+            AtlasPageDesc::new("straight.png", page.width, page.height),
+            AtlasPageDesc::new("premultiplied.png", page.width, page.height).with_pma(true),
+        ];
+        for (index, region) in page.regions.into_iter().enumerate() {
+            pages[index % 2].regions.push(region);
+        }
+        let [straight, premultiplied] = pages;
+        let premultiplied_names: Vec<String> = premultiplied
+            .regions
+            .iter()
+            .map(|region| region.name.clone())
+            .collect();
+        let atlas: Atlas = AtlasBuilder::new()
+            .with_page(straight)
+            .with_page(premultiplied)
+            .build("")
+            .unwrap();
+        let skeleton_data = SkeletonJson::new(Arc::new(atlas))
+            .read_skeleton_data(asset.json_data)
+            .unwrap();
+        let mut skeleton = Skeleton::new(Arc::new(skeleton_data));
+        skeleton.update_world_transform();
+
+        let simple = SimpleDrawer {
+            cull_direction: CullDirection::Clockwise,
+            premultiplied_alpha: false,
+            premultiplied_alpha_from_atlas: true,
+            skip_pending_textures: false,
+            color_space: ColorSpace::SRGB,
+        }
+        .draw(&mut skeleton, None);
+        let mut expected_batches = 0;
+        let mut previous = None;
+        for renderable in &simple {
+            let slot = skeleton.slot_at_index(renderable.slot_index).unwrap();
+            let attachment = slot.attachment().unwrap();
+            let region_name = unsafe {
+                if let Some(mesh_attachment) = attachment.as_mesh() {
+                    let mut renderer_object = mesh_attachment.renderer_object();
+                    renderer_object
+                        .get_atlas_region()
+                        .unwrap()
+                        .name()
+                        .to_owned()
+                } else {
+                    let region_attachment = attachment.as_region().unwrap();
+                    let mut renderer_object = region_attachment.renderer_object();
+                    renderer_object
+                        .get_atlas_region()
+                        .unwrap()
+                        .name()
+                        .to_owned()
+                }
+            };
+            assert_eq!(
+                renderable.premultiplied_alpha,
+                premultiplied_names.contains(&region_name)
+            );
+            if previous != Some((renderable.blend_mode, renderable.premultiplied_alpha)) {
+                expected_batches += 1;
+                previous = Some((renderable.blend_mode, renderable.premultiplied_alpha));
+            }
+        }
+        assert!(simple
+            .iter()
+            .any(|renderable| renderable.premultiplied_alpha));
+        assert!(simple
+            .iter()
+            .any(|renderable| !renderable.premultiplied_alpha));
+
+        let combined = CombinedDrawer {
+            cull_direction: CullDirection::Clockwise,
+            premultiplied_alpha: false,
+            premultiplied_alpha_from_atlas: true,
+            skip_pending_textures: false,
+            color_space: ColorSpace::SRGB,
+        }
+        .draw(&mut skeleton, None);
+        assert_eq!(combined.len(), expected_batches);
+        for pair in combined.windows(2) {
+            assert!(
+                pair[0].blend_mode != pair[1].blend_mode
+                    || pair[0].premultiplied_alpha != pair[1].premultiplied_alpha
+            );
+        }
+    }
+
+    /// The first batch takes its settings from the first attachment drawn, so slots skipped before
+    /// it do not leave an empty renderable with the default settings.
+    #[cfg(not(feature = "spine38"))]
+    #[test]
+    fn combined_drawer_first_batch() {
+        use std::sync::Arc;
+
+        use crate::{atlas::AtlasBuilder, SkeletonJson};
+
+        let asset = TestAsset::spineboy();
+        let mut builder = AtlasBuilder::from_atlas(&asset.atlas());
+        for page in &mut builder.pages {
+            page.pma = true;
+        }
+        let skeleton_data = SkeletonJson::new(Arc::new(builder.build("").unwrap()))
+            .read_skeleton_data(asset.json_data)
+            .unwrap();
+        let mut skeleton = Skeleton::new(Arc::new(skeleton_data));
+        skeleton.update_world_transform();
+        assert!(skeleton
+            .draw_order_at_index(0)
+            .unwrap()
+            .attachment()
+            .is_none());
+
+        let renderables = CombinedDrawer {
+            cull_direction: CullDirection::Clockwise,
+            premultiplied_alpha: false,
+            premultiplied_alpha_from_atlas: true,
+            skip_pending_textures: false,
+            color_space: ColorSpace::SRGB,
+        }
+        .draw(&mut skeleton, None);
+        assert_eq!(renderables.len(), 1);
+        assert!(renderables[0].premultiplied_alpha);
+        assert!(!renderables[0].indices.is_empty());
+    }
+
+    /// Attachments on pages without a texture are skipped only if requested.
+    #[test]
+    fn combined_drawer_skip_pending_textures() {
+        use std::sync::Arc;
+
+        use crate::{Atlas, Skeleton, SkeletonJson};
+
+        struct Texture(#[allow(dead_code)] String);
+
+        let asset = TestAsset::dragon();
+        let atlas = Arc::new(Atlas::new_deferred(asset.atlas_data, "").unwrap());
+        let skeleton_data = SkeletonJson::new(atlas.clone())
+            .read_skeleton_data(asset.json_data)
+            .unwrap();
+        let mut skeleton = Skeleton::new(Arc::new(skeleton_data));
+        skeleton.set_to_setup_pose();
+        skeleton.update_world_transform();
+        let draw = |skeleton: &mut Skeleton, skip_pending_textures: bool| {
+            CombinedDrawer {
+                cull_direction: CullDirection::Clockwise,
+                premultiplied_alpha: false,
+                premultiplied_alpha_from_atlas: false,
+                skip_pending_textures,
+                color_space: ColorSpace::SRGB,
+            }
+            .draw(skeleton, None)
+        };
+
+        let pending_pages = atlas.pending_pages();
+        assert!(pending_pages.len() > 1);
+        assert!(draw(&mut skeleton, true).is_empty());
+        let first = &pending_pages[0];
+        first
+            .handle
+            .get(&atlas)
+            .unwrap()
+            .set_texture(Texture(first.path.clone()));
+        assert_eq!(atlas.pending_pages().len(), pending_pages.len() - 1);
+        let skipped = draw(&mut skeleton, true);
+        let drawn = draw(&mut skeleton, false);
+        assert!(!skipped.is_empty());
+        assert!(skipped
+            .iter()
+            .all(|renderable| renderable.attachment_renderer_object.is_some()));
+        assert!(drawn
+            .iter()
+            .any(|renderable| renderable.attachment_renderer_object.is_none()));
+
+        for pending_page in atlas.pending_pages() {
+            let page = pending_page.handle.get(&atlas).unwrap();
+            page.set_texture(Texture(pending_page.path));
+        }
+        assert!(atlas.pending_pages().is_empty());
+        assert_eq!(
+            draw(&mut skeleton, true).len(),
+            draw(&mut skeleton, false).len()
+        );
+        for page in atlas.pages() {
+            unsafe { page.renderer_object().dispose::<Texture>() };
+        }
+    }
+
+    /// Ensure owned renderables survive a serialization round trip.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn combined_renderable_data_serde() {
+        let (mut skeleton, _) = TestAsset::spineboy().instance();
+        let drawer = CombinedDrawer {
+            cull_direction: CullDirection::Clockwise,
+            premultiplied_alpha: false,
+            premultiplied_alpha_from_atlas: false,
+            skip_pending_textures: false,
+            color_space: ColorSpace::Linear,
+        };
+        let renderables: Vec<CombinedRenderableData> = drawer
+            .draw(&mut skeleton, None)
+            .into_iter()
+            .map(CombinedRenderableData::from)
+            .collect();
+        let json = serde_json::to_string(&renderables).unwrap();
+        let deserialized: Vec<CombinedRenderableData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(renderables, deserialized);
+    }
 }
@@ -12,8 +12,11 @@ mod simple;
 pub use combined::*;
 pub use simple::*;
 
+use crate::Slot;
+
 /// Cull direction to use with helper draw functions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CullDirection {
     Clockwise,
     CounterClockwise,
@@ -21,7 +24,56 @@ pub enum CullDirection {
 
 /// Color space to use with helper draw functions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorSpace {
     SRGB,
     Linear,
 }
+
+/// Whether the texture of a slot's attachment has premultiplied alpha, according to the atlas
+/// page of its region. Falls back to `default` if `from_atlas` is false, if the attachment does
+/// not have an atlas region, or if using Spine 3.8 which does not store this setting.
+#[cfg(not(feature = "spine38"))]
+pub(crate) fn premultiplied_alpha(slot: &Slot, from_atlas: bool, default: bool) -> bool {
+    if from_atlas {
+        let pma = slot.attachment().and_then(|attachment| unsafe {
+            if let Some(mesh_attachment) = attachment.as_mesh() {
+                let mut renderer_object = mesh_attachment.renderer_object();
+                let region = renderer_object.get_atlas_region()?;
+                Some(region.page().pma())
+            } else {
+                let region_attachment = attachment.as_region()?;
+                let mut renderer_object = region_attachment.renderer_object();
+                let region = renderer_object.get_atlas_region()?;
+                Some(region.page().pma())
+            }
+        });
+        return pma.unwrap_or(default);
+    }
+    default
+}
+
+#[cfg(feature = "spine38")]
+pub(crate) const fn premultiplied_alpha(_slot: &Slot, _from_atlas: bool, default: bool) -> bool {
+    default
+}
+
+/// Whether a slot's attachment draws from an atlas page which does not have a texture yet, see
+/// [`Atlas::new_deferred`](`crate::Atlas::new_deferred`). Attachments with a sequence only point
+/// at the region for the current frame once their world vertices are computed, so this should be
+/// checked afterwards.
+pub(crate) fn texture_pending(slot: &Slot) -> bool {
+    let pending = slot.attachment().and_then(|attachment| unsafe {
+        if let Some(mesh_attachment) = attachment.as_mesh() {
+            let mut renderer_object = mesh_attachment.renderer_object();
+            let region = renderer_object.get_atlas_region()?;
+            Some(!region.page().texture_ready())
+        } else {
+            let region_attachment = attachment.as_region()?;
+            let mut renderer_object = region_attachment.renderer_object();
+            let region = renderer_object.get_atlas_region()?;
+            Some(!region.page().texture_ready())
+        }
+    });
+    pending.unwrap_or(false)
+}
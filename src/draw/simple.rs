@@ -29,11 +29,62 @@ pub struct SimpleRenderable {
     pub dark_color: Color,
     /// The blend mode to use when drawing this mesh.
     pub blend_mode: BlendMode,
+    /// Whether the colors of this mesh are premultiplied, and its texture is expected to have
+    /// premultiplied alpha.
+    pub premultiplied_alpha: bool,
     /// The attachment's renderer object as a raw pointer. Usually represents the texture created
     /// from [`extension::set_create_texture_cb`].
     pub attachment_renderer_object: Option<*const c_void>,
 }
 
+/// An owned copy of a [`SimpleRenderable`] without the attachment's renderer object pointer.
+///
+/// Useful for storing or sending draw data somewhere the renderer object is meaningless, such as
+/// another process. If the `serde` feature is enabled, this type can be serialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleRenderableData {
+    /// The index of the slot in [`Skeleton`] that this renderable represents.
+    pub slot_index: usize,
+    /// A list of vertex attributes for a mesh.
+    pub vertices: Vec<[f32; 2]>,
+    /// A list of UV attributes for a mesh.
+    pub uvs: Vec<[f32; 2]>,
+    /// A list of indices for a mesh.
+    pub indices: Vec<u16>,
+    /// The color tint of the mesh.
+    pub color: Color,
+    /// The dark color tint of the mesh.
+    /// See the [Spine User Guide](http://en.esotericsoftware.com/spine-slots#Tint-black).
+    pub dark_color: Color,
+    /// The blend mode to use when drawing this mesh.
+    pub blend_mode: BlendMode,
+    /// Whether the colors of this mesh are premultiplied, and its texture is expected to have
+    /// premultiplied alpha.
+    pub premultiplied_alpha: bool,
+}
+
+impl From<SimpleRenderable> for SimpleRenderableData {
+    fn from(renderable: SimpleRenderable) -> Self {
+        Self {
+            slot_index: renderable.slot_index,
+            vertices: renderable.vertices,
+            uvs: renderable.uvs,
+            indices: renderable.indices,
+            color: renderable.color,
+            dark_color: renderable.dark_color,
+            blend_mode: renderable.blend_mode,
+            premultiplied_alpha: renderable.premultiplied_alpha,
+        }
+    }
+}
+
+impl From<&SimpleRenderable> for SimpleRenderableData {
+    fn from(renderable: &SimpleRenderable) -> Self {
+        renderable.clone().into()
+    }
+}
+
 /// A simple drawer with no optimizations.
 ///
 /// Assumes use of the default atlas attachment loader.
@@ -44,6 +95,14 @@ pub struct SimpleDrawer {
     pub cull_direction: CullDirection,
     /// Set to `true` if the textures are expected to have premultiplied alpha.
     pub premultiplied_alpha: bool,
+    /// Set to `true` to read the premultiplied alpha setting of each attachment from its atlas
+    /// page instead, using [`SimpleDrawer::premultiplied_alpha`] for attachments without one.
+    /// Spine 3.8 atlases do not store this setting.
+    pub premultiplied_alpha_from_atlas: bool,
+    /// Set to `true` to skip attachments whose atlas page does not have a texture yet, see
+    /// [`Atlas::new_deferred`](`crate::Atlas::new_deferred`). Otherwise these attachments are
+    /// drawn with no renderer object.
+    pub skip_pending_textures: bool,
     /// The color space to use for the colors returned in [`SimpleRenderable`].
     pub color_space: ColorSpace,
 }
@@ -82,7 +141,7 @@ impl SimpleDrawer {
             let mut uvs = vec![];
             let mut color;
 
-            if let Some(mesh_attachment) = slot.attachment().and_then(|a| a.as_mesh()) {
+            if let Some(mesh_attachment) = slot.mesh_attachment() {
                 #[cfg(feature = "spine38")]
                 unsafe {
                     spMeshAttachment_updateUVs(mesh_attachment.c_ptr());
@@ -165,7 +224,7 @@ impl SimpleDrawer {
                         }
                     }
                 }
-            } else if let Some(region_attachment) = slot.attachment().and_then(|a| a.as_region()) {
+            } else if let Some(region_attachment) = slot.region_attachment() {
                 color = region_attachment.color();
 
                 let mut world_vertices = vec![];
@@ -214,9 +273,7 @@ impl SimpleDrawer {
                     indices.push(3);
                     indices.push(0);
                 }
-            } else if let Some(clipping_attachment) =
-                slot.attachment().and_then(|a| a.as_clipping())
-            {
+            } else if let Some(clipping_attachment) = slot.clipping_attachment() {
                 if let Some(clipper) = clipper.as_deref_mut() {
                     clipper.clip_start(&slot, &clipping_attachment);
                 }
@@ -228,6 +285,13 @@ impl SimpleDrawer {
                 continue;
             }
 
+            if self.skip_pending_textures && super::texture_pending(&slot) {
+                if let Some(clipper) = clipper.as_deref_mut() {
+                    clipper.clip_end(&slot);
+                }
+                continue;
+            }
+
             if let Some(clipper) = clipper.as_deref_mut() {
                 if clipper.is_clipping() {
                     unsafe {
@@ -264,46 +328,49 @@ impl SimpleDrawer {
                 }
             }
 
-            let attachment_renderer_object =
-                slot.attachment().and_then(|a| a.as_mesh()).map_or_else(
-                    || {
-                        slot.attachment().and_then(|a| a.as_region()).and_then(
-                            |region_attachment| unsafe {
-                                let attachment_renderer_object = region_attachment
-                                    .renderer_object()
-                                    .get_atlas_region()
-                                    .unwrap()
-                                    .page()
-                                    .c_ptr_ref()
-                                    .rendererObject
-                                    .cast_const();
-                                if attachment_renderer_object.is_null() {
-                                    None
-                                } else {
-                                    Some(attachment_renderer_object)
-                                }
-                            },
-                        )
-                    },
-                    |mesh_attachment| unsafe {
-                        let attachment_renderer_object = mesh_attachment
-                            .renderer_object()
-                            .get_atlas_region()
-                            .unwrap()
-                            .page()
-                            .c_ptr_ref()
-                            .rendererObject
-                            .cast_const();
-                        if attachment_renderer_object.is_null() {
-                            None
-                        } else {
-                            Some(attachment_renderer_object)
-                        }
-                    },
-                );
+            let attachment_renderer_object = slot.mesh_attachment().map_or_else(
+                || {
+                    slot.region_attachment()
+                        .and_then(|region_attachment| unsafe {
+                            let attachment_renderer_object = region_attachment
+                                .renderer_object()
+                                .get_atlas_region()
+                                .unwrap()
+                                .page()
+                                .c_ptr_ref()
+                                .rendererObject
+                                .cast_const();
+                            if attachment_renderer_object.is_null() {
+                                None
+                            } else {
+                                Some(attachment_renderer_object)
+                            }
+                        })
+                },
+                |mesh_attachment| unsafe {
+                    let attachment_renderer_object = mesh_attachment
+                        .renderer_object()
+                        .get_atlas_region()
+                        .unwrap()
+                        .page()
+                        .c_ptr_ref()
+                        .rendererObject
+                        .cast_const();
+                    if attachment_renderer_object.is_null() {
+                        None
+                    } else {
+                        Some(attachment_renderer_object)
+                    }
+                },
+            );
 
+            let premultiplied_alpha = super::premultiplied_alpha(
+                &slot,
+                self.premultiplied_alpha_from_atlas,
+                self.premultiplied_alpha,
+            );
             color *= slot.color() * skeleton.color();
-            if self.premultiplied_alpha {
+            if premultiplied_alpha {
                 color.premultiply_alpha();
             }
             color = match self.color_space {
@@ -314,7 +381,7 @@ impl SimpleDrawer {
             let mut dark_color = slot
                 .dark_color()
                 .unwrap_or_else(|| Color::new_rgba(0.0, 0.0, 0.0, 0.0));
-            if self.premultiplied_alpha {
+            if premultiplied_alpha {
                 dark_color.a = 1.0;
                 dark_color.premultiply_alpha();
             } else {
@@ -333,6 +400,7 @@ impl SimpleDrawer {
                 color,
                 dark_color,
                 blend_mode: slot.data().blend_mode(),
+                premultiplied_alpha,
                 attachment_renderer_object,
             });
             if let Some(clipper) = clipper.as_deref_mut() {
@@ -361,6 +429,8 @@ mod test {
             let drawer = SimpleDrawer {
                 cull_direction: CullDirection::Clockwise,
                 premultiplied_alpha: false,
+                premultiplied_alpha_from_atlas: false,
+                skip_pending_textures: false,
                 color_space: ColorSpace::Linear,
             };
             let mut clipper = SkeletonClipping::new();
@@ -1,4 +1,4 @@
-use std::{error, ffi::NulError, fmt};
+use std::{error, ffi::NulError, fmt, path::Path};
 
 /// Error types when interacting with the Spine runtime.
 #[derive(Debug)]
@@ -15,6 +15,17 @@ pub enum SpineError {
     PathNotUtf8,
     /// Failed to create the requested type.
     CreationFailed { what: String },
+    /// An atlas file was malformed, see [`Atlas::new_strict`](`crate::Atlas::new_strict`).
+    InvalidAtlas { line: usize, reason: String },
+    /// An error when failing to write files.
+    FailedToWriteFile { file: String },
+    /// An image was too large to fit on an atlas page, see
+    /// [`TexturePacker`](`crate::texture_packer::TexturePacker`).
+    ImageTooLarge {
+        name: String,
+        width: u32,
+        height: u32,
+    },
 }
 
 impl SpineError {
@@ -36,6 +47,19 @@ impl SpineError {
             what: what.to_owned(),
         }
     }
+
+    pub(crate) fn new_failed_to_read_file(path: &Path) -> Self {
+        Self::FailedToReadFile {
+            file: path.to_string_lossy().into_owned(),
+        }
+    }
+
+    #[cfg(feature = "texture_packer")]
+    pub(crate) fn new_failed_to_write_file(path: &Path) -> Self {
+        Self::FailedToWriteFile {
+            file: path.to_string_lossy().into_owned(),
+        }
+    }
 }
 
 impl From<NulError> for SpineError {
@@ -72,6 +96,25 @@ impl fmt::Display for SpineError {
                 write!(f, "Failed to create {what}")?;
                 Ok(())
             }
+            SpineError::InvalidAtlas { line, reason } => {
+                write!(f, "Invalid atlas on line {line}: {reason}")?;
+                Ok(())
+            }
+            Self::FailedToWriteFile { file } => {
+                write!(f, "Failed to write file: {file}")?;
+                Ok(())
+            }
+            SpineError::ImageTooLarge {
+                name,
+                width,
+                height,
+            } => {
+                write!(
+                    f,
+                    "Image too large for atlas page: {name} ({width}x{height})"
+                )?;
+                Ok(())
+            }
         }
     }
 }
@@ -1,39 +1,55 @@
 use crate::{
     c::{spEvent, spEventData},
-    c_interface::{NewFromPtr, SyncPtr},
-    TrackEntry,
+    c_interface::{CTmpRef, NewFromPtr, SyncPtr},
+    AnimationState, TrackEntry,
 };
 
-#[allow(unused_imports)]
-use crate::AnimationState;
-
 /// A wrapper for [`Event`] that makes events slightly nicer to work with in Rust.
 ///
 /// To receive events, see [`AnimationState::set_listener`].
+///
+/// The track entry and event are borrowed from the [`AnimationState`] for the duration of the
+/// listener call, since the runtime may dispose of them right after. To refer to a track entry
+/// later, keep a [`TrackEntryHandle`](`crate::TrackEntryHandle`) instead:
+///
+/// ```compile_fail
+/// # #[path="./test.rs"]
+/// # mod test;
+/// # use std::sync::{Arc, Mutex};
+/// # use rusty_spine::AnimationEvent;
+/// # let (_, mut animation_state) = test::TestAsset::spineboy().instance();
+/// let disposed = Arc::new(Mutex::new(vec![]));
+/// let disposed_clone = disposed.clone();
+/// animation_state.set_listener(move |_, animation_event| {
+///     if let AnimationEvent::Dispose { track_entry } = animation_event {
+///         disposed_clone.lock().unwrap().push(track_entry);
+///     }
+/// });
+/// ```
 pub enum AnimationEvent<'a> {
     Start {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
     },
     Interrupt {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
     },
     End {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
     },
     Complete {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
     },
     Dispose {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
     },
     Event {
         /// The track this event originated from.
-        track_entry: TrackEntry,
+        track_entry: CTmpRef<'a, AnimationState, TrackEntry>,
         /// The name of the event, which is unique across all events in the skeleton.
         name: &'a str,
         /// The animation time this event was keyed.
@@ -51,7 +67,7 @@ pub enum AnimationEvent<'a> {
         /// The event's audio balance.
         balance: f32,
         /// The raw event data.
-        event: Event,
+        event: CTmpRef<'a, AnimationState, Event>,
     },
 }
 
@@ -11,13 +11,24 @@
 //! * `void _spAtlasPage_disposeTexture (spAtlasPage* self);`
 //! * `char* _spUtil_readFile (const char* path, int* length);`
 //!
-//! They can be set with the functions found on this page.
+//! They can be set globally with the functions found on this page, or provided per
+//! [`Atlas`](`crate::Atlas`) or per scope with [`ExtensionCallbacks`], which take precedence over
+//! the global callbacks.
+//!
+//! The callbacks are called on whichever thread loads or drops an atlas, so the global callbacks
+//! must be [`Send`], and [`ExtensionCallbacks`] must be [`Send`] and [`Sync`]. See
+//! [Threading](`crate#threading`).
 //!
 //! You can read more about these functions on the
 //! [spine-c Runtime Docs](http://en.esotericsoftware.com/spine-c#Integrating-spine-c-in-your-engine).
 
+use std::cell::RefCell;
 use std::ffi::CStr;
+use std::fmt;
 use std::fs::read;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Once};
 
 use crate::c::{c_int, c_void, size_t};
@@ -25,11 +36,16 @@ use crate::c_interface::NewFromPtr;
 use crate::{
     atlas::AtlasPage,
     c::{c_char, spAtlasPage},
+    fs::SpineFs,
 };
 
-type CreateTextureCb = Box<dyn Fn(&mut AtlasPage, &str)>;
-type DisposeTextureCb = Box<dyn Fn(&mut AtlasPage)>;
-type ReadFileCb = Box<dyn Fn(&str) -> Option<Vec<u8>>>;
+type CreateTextureCb = Box<dyn Fn(&mut AtlasPage, &str) + Send>;
+type DisposeTextureCb = Box<dyn Fn(&mut AtlasPage) + Send>;
+type ReadFileCb = Box<dyn Fn(&str) -> Option<Vec<u8>> + Send>;
+
+type ScopedCreateTextureCb = Box<dyn Fn(&mut AtlasPage, &str) + Send + Sync>;
+type ScopedDisposeTextureCb = Box<dyn Fn(&mut AtlasPage) + Send + Sync>;
+type ScopedReadFileCb = Box<dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync>;
 
 #[derive(Default)]
 pub(crate) struct Extension {
@@ -52,6 +68,140 @@ impl Extension {
     }
 }
 
+/// A set of extension callbacks which take precedence over the global callbacks, either for a
+/// single atlas (see [`Atlas::new_with_extension`](`crate::Atlas::new_with_extension`)) or for
+/// everything loaded on the current thread while a [`scope`] is active.
+///
+/// Callbacks which are not set fall back to any enclosing scope, and then to the global callbacks.
+///
+/// ```
+/// use std::sync::Arc;
+/// use rusty_spine::extension::{self, ExtensionCallbacks};
+///
+/// struct PreviewTexture(pub String);
+///
+/// let preview = Arc::new(
+///     ExtensionCallbacks::new()
+///         .with_create_texture_cb(|atlas_page, path| {
+///             atlas_page.renderer_object().set(PreviewTexture(path.to_owned()));
+///         })
+///         .with_dispose_texture_cb(|atlas_page| unsafe {
+///             atlas_page.renderer_object().dispose::<PreviewTexture>();
+///         }),
+/// );
+/// let _scope = extension::scope(preview);
+/// // Atlases loaded on this thread now use the preview textures.
+/// ```
+#[derive(Default)]
+pub struct ExtensionCallbacks {
+    create_texture_cb: Option<ScopedCreateTextureCb>,
+    dispose_texture_cb: Option<ScopedDisposeTextureCb>,
+    read_file_cb: Option<ScopedReadFileCb>,
+}
+
+impl ExtensionCallbacks {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the callback used in place of [`set_create_texture_cb`].
+    #[must_use]
+    pub fn with_create_texture_cb<F>(mut self, create_texture_cb: F) -> Self
+    where
+        F: Fn(&mut AtlasPage, &str) + Send + Sync + 'static,
+    {
+        self.create_texture_cb = Some(Box::new(create_texture_cb));
+        self
+    }
+
+    /// Set the callback used in place of [`set_dispose_texture_cb`].
+    #[must_use]
+    pub fn with_dispose_texture_cb<F>(mut self, dispose_texture_cb: F) -> Self
+    where
+        F: Fn(&mut AtlasPage) + Send + Sync + 'static,
+    {
+        self.dispose_texture_cb = Some(Box::new(dispose_texture_cb));
+        self
+    }
+
+    /// Read files from `fs` in place of [`set_read_file_cb`], see [`SpineFs`].
+    #[must_use]
+    pub fn with_fs(self, fs: Arc<dyn SpineFs>) -> Self {
+        self.with_read_file_cb(move |path| fs.read(Path::new(path)).ok())
+    }
+
+    /// Set the callback used in place of [`set_read_file_cb`].
+    #[must_use]
+    pub fn with_read_file_cb<F>(mut self, read_file_cb: F) -> Self
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.read_file_cb = Some(Box::new(read_file_cb));
+        self
+    }
+}
+
+impl fmt::Debug for ExtensionCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtensionCallbacks")
+            .field("create_texture_cb", &self.create_texture_cb.is_some())
+            .field("dispose_texture_cb", &self.dispose_texture_cb.is_some())
+            .field("read_file_cb", &self.read_file_cb.is_some())
+            .finish()
+    }
+}
+
+thread_local! {
+    static SCOPES: RefCell<Vec<(usize, Arc<ExtensionCallbacks>)>> = const { RefCell::new(vec![]) };
+}
+
+/// Use `callbacks` in place of the global callbacks on the current thread until the returned
+/// guard is dropped. Scopes can be nested, with the innermost scope taking precedence.
+///
+/// An atlas keeps the scope which created its textures, so they are disposed with the same
+/// callbacks even if the atlas is dropped after the scope ends.
+#[must_use = "the scope ends when the guard is dropped"]
+pub fn scope(callbacks: Arc<ExtensionCallbacks>) -> ExtensionScope {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    SCOPES.with(|scopes| scopes.borrow_mut().push((id, callbacks)));
+    ExtensionScope {
+        id,
+        _not_send: PhantomData,
+    }
+}
+
+/// A guard which ends a scope started with [`scope`] when dropped.
+#[derive(Debug)]
+pub struct ExtensionScope {
+    id: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ExtensionScope {
+    fn drop(&mut self) {
+        SCOPES.with(|scopes| scopes.borrow_mut().retain(|(id, _)| *id != self.id));
+    }
+}
+
+/// The scope which would create textures for an atlas loaded now, for the atlas to keep.
+pub(crate) fn texture_scope() -> Option<Arc<ExtensionCallbacks>> {
+    find_scoped(|callbacks| callbacks.create_texture_cb.is_some())
+}
+
+/// Find the innermost scope on this thread with a callback set.
+fn find_scoped(has_cb: fn(&ExtensionCallbacks) -> bool) -> Option<Arc<ExtensionCallbacks>> {
+    SCOPES.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(_, callbacks)| has_cb(callbacks))
+            .map(|(_, callbacks)| callbacks.clone())
+    })
+}
+
 /// Set `_spAtlasPage_createTexture`
 ///
 /// The purpose of this callback is to allow loading textures in whichever engine is being used.
@@ -104,7 +254,7 @@ impl Extension {
 /// Panics if the internal mutex is poisoned.
 pub fn set_create_texture_cb<F>(create_texture_cb: F)
 where
-    F: Fn(&mut AtlasPage, &str) + 'static,
+    F: Fn(&mut AtlasPage, &str) + Send + 'static,
 {
     let singleton = Extension::singleton();
     let mut extension = singleton.lock().unwrap();
@@ -120,7 +270,7 @@ where
 /// Panics if the internal mutex is poisoned.
 pub fn set_dispose_texture_cb<F>(dispose_texture_cb: F)
 where
-    F: Fn(&mut AtlasPage) + 'static,
+    F: Fn(&mut AtlasPage) + Send + 'static,
 {
     let singleton = Extension::singleton();
     let mut extension = singleton.lock().unwrap();
@@ -145,7 +295,7 @@ where
 /// Panics if the internal mutex is poisoned.
 pub fn set_read_file_cb<F>(read_file_cb: F)
 where
-    F: Fn(&str) -> Option<Vec<u8>> + 'static,
+    F: Fn(&str) -> Option<Vec<u8>> + Send + 'static,
 {
     let singleton = Extension::singleton();
     let mut extension = singleton.lock().unwrap();
@@ -154,20 +304,39 @@ where
 
 #[no_mangle]
 extern "C" fn _spAtlasPage_createTexture(c_atlas_page: *mut spAtlasPage, c_path: *const c_char) {
+    unsafe {
+        create_texture(
+            &mut AtlasPage::new_from_ptr(c_atlas_page),
+            CStr::from_ptr(c_path).to_str().unwrap(),
+        );
+    }
+}
+
+/// Call the create texture callback of the innermost scope, or the global callback.
+pub(crate) fn create_texture(atlas_page: &mut AtlasPage, path: &str) {
+    if let Some(callbacks) = find_scoped(|callbacks| callbacks.create_texture_cb.is_some()) {
+        if let Some(cb) = &callbacks.create_texture_cb {
+            cb(atlas_page, path);
+        }
+        return;
+    }
     let singleton = Extension::singleton();
     let extension = singleton.lock().unwrap();
     if let Some(cb) = &extension.create_texture_cb {
-        unsafe {
-            cb(
-                &mut AtlasPage::new_from_ptr(c_atlas_page),
-                CStr::from_ptr(c_path).to_str().unwrap(),
-            );
-        }
+        cb(atlas_page, path);
     }
 }
 
 #[no_mangle]
 extern "C" fn _spAtlasPage_disposeTexture(c_atlas_page: *mut spAtlasPage) {
+    if let Some(callbacks) = find_scoped(|callbacks| callbacks.dispose_texture_cb.is_some()) {
+        if let Some(cb) = &callbacks.dispose_texture_cb {
+            unsafe {
+                cb(&mut AtlasPage::new_from_ptr(c_atlas_page));
+            }
+        }
+        return;
+    }
     let singleton = Extension::singleton();
     let extension = singleton.lock().unwrap();
     if let Some(cb) = &extension.dispose_texture_cb {
@@ -182,32 +351,108 @@ extern "C" {
     fn spine_memcpy(__dest: *mut c_void, __src: *const c_void, __n: size_t) -> *mut c_void;
 }
 
+/// Copy file data into memory owned by the C runtime.
+unsafe fn to_c_data(data: &[u8], c_length: *mut c_int) -> *mut c_char {
+    let c_data = spine_malloc(data.len() as size_t);
+    spine_memcpy(c_data, data.as_ptr().cast::<c_void>(), data.len() as size_t);
+    *c_length = data.len() as c_int;
+    c_data.cast::<c_char>()
+}
+
 #[no_mangle]
 extern "C" fn _spUtil_readFile(c_path: *const c_char, c_length: *mut c_int) -> *mut c_char {
+    let path = unsafe { CStr::from_ptr(c_path).to_str().unwrap() };
+    if let Some(callbacks) = find_scoped(|callbacks| callbacks.read_file_cb.is_some()) {
+        if let Some(cb) = &callbacks.read_file_cb {
+            return cb(path).map_or(std::ptr::null_mut(), |data| unsafe {
+                to_c_data(&data, c_length)
+            });
+        }
+    }
     let singleton = Extension::singleton();
     let extension = singleton.lock().unwrap();
     extension.read_file_cb.as_ref().map_or_else(
         || {
-            let str = unsafe { CStr::from_ptr(c_path).to_str().unwrap().to_owned() };
-            read(str).map_or(std::ptr::null_mut(), |data| {
-                let c_data = unsafe { spine_malloc(data.len() as size_t) };
-                unsafe {
-                    spine_memcpy(c_data, data.as_ptr().cast::<c_void>(), data.len() as size_t);
-                    *c_length = data.len() as c_int;
-                }
-                c_data.cast::<c_char>()
+            read(path).map_or(std::ptr::null_mut(), |data| unsafe {
+                to_c_data(&data, c_length)
             })
         },
         |cb| {
-            cb(unsafe { CStr::from_ptr(c_path).to_str().unwrap() }).map_or(
-                std::ptr::null_mut(),
-                |data| unsafe {
-                    *c_length = data.len() as c_int;
-                    let c_data = spine_malloc(data.len() as size_t);
-                    spine_memcpy(c_data, data.as_ptr().cast::<c_void>(), data.len() as size_t);
-                    c_data.cast::<c_char>()
-                },
-            )
+            cb(path).map_or(std::ptr::null_mut(), |data| unsafe {
+                to_c_data(&data, c_length)
+            })
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test::TestAsset, Atlas};
+
+    use super::*;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    fn recording_callbacks(log: &Log, tag: &'static str) -> ExtensionCallbacks {
+        let (create_log, dispose_log) = (log.clone(), log.clone());
+        ExtensionCallbacks::new()
+            .with_create_texture_cb(move |_, path| {
+                create_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{tag} create {path}"));
+            })
+            .with_dispose_texture_cb(move |atlas_page| {
+                let name = atlas_page.name().to_owned();
+                dispose_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{tag} dispose {name}"));
+            })
+    }
+
+    /// Check that atlases created with different callbacks create and dispose their own textures.
+    #[test]
+    fn extension_per_atlas() {
+        let log = Log::default();
+        let asset = TestAsset::spineboy();
+        let editor = Arc::new(recording_callbacks(&log, "editor"));
+        let game = Arc::new(recording_callbacks(&log, "game"));
+        let editor_atlas = Atlas::new_with_extension(asset.atlas_data, "editor", editor).unwrap();
+        let game_atlas = Atlas::new_from_file_with_extension(asset.atlas_file, game).unwrap();
+        drop(editor_atlas);
+        drop(game_atlas);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "editor create editor/spineboy.png",
+                "game create assets/spineboy/export/spineboy.png",
+                "editor dispose spineboy.png",
+                "game dispose spineboy.png",
+            ]
+        );
+    }
+
+    /// Check that nested scopes fall back to enclosing scopes for callbacks they do not set.
+    #[test]
+    fn extension_scope() {
+        let log = Log::default();
+        let atlas_data = TestAsset::spineboy().atlas_data;
+        let outer = Arc::new(
+            recording_callbacks(&log, "outer").with_read_file_cb(move |path| {
+                (path == "virtual.atlas").then(|| atlas_data.to_vec())
+            }),
+        );
+        let outer_scope = scope(outer);
+        let inner_scope = scope(Arc::new(recording_callbacks(&log, "inner")));
+        let atlas = Atlas::new_from_file("virtual.atlas").unwrap();
+        drop(inner_scope);
+        drop(atlas);
+        drop(outer_scope);
+        assert!(Atlas::new_from_file("virtual.atlas").is_err());
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["inner create spineboy.png", "inner dispose spineboy.png"]
+        );
+    }
+}
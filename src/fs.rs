@@ -0,0 +1,347 @@
+//! A virtual filesystem for loading atlases, skeletons, and atlas page images.
+//!
+//! Spine files reference each other by relative path, such as the image file of each atlas page.
+//! A [`SpineFs`] reads files and resolves these references, so the same loading code works with
+//! the real filesystem ([`RealFs`]), files held in memory ([`MemoryFs`]), or a zip archive
+//! (`ZipFs`, which requires the `zip` feature).
+//!
+//! ```
+//! # #[path="./test.rs"]
+//! # mod test;
+//! use std::sync::Arc;
+//! use rusty_spine::{fs::MemoryFs, Atlas, SkeletonJson};
+//!
+//! # let asset = test::TestAsset::spineboy();
+//! let fs = MemoryFs::new()
+//!     .with_file("spineboy/spineboy.atlas", asset.atlas_data)
+//!     .with_file("spineboy/spineboy.json", asset.json_data);
+//! let atlas = Arc::new(Atlas::new_from_fs(&fs, "spineboy/spineboy.atlas")?);
+//! let skeleton_json = SkeletonJson::new(atlas.clone());
+//! let skeleton_data = skeleton_json.read_skeleton_data_fs(&fs, "spineboy/spineboy.json")?;
+//! # Ok::<(), rusty_spine::SpineError>(())
+//! ```
+//!
+//! The paths passed to the create texture callback (see
+//! [`extension::set_create_texture_cb`](`crate::extension::set_create_texture_cb`)) are resolved
+//! relative to the atlas with [`SpineFs::resolve`], and can be read with the same filesystem.
+//! To use a filesystem for functions which load files by path, such as
+//! [`Atlas::new_from_file`](`crate::Atlas::new_from_file`), see
+//! [`ExtensionCallbacks::with_fs`](`crate::extension::ExtensionCallbacks::with_fs`).
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+#[cfg(feature = "zip")]
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    sync::Mutex,
+};
+
+/// A source of files for loading Spine data.
+pub trait SpineFs: Send + Sync {
+    /// Read the entire contents of the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not exist or could not be read.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Resolve `relative`, a path referenced by the file at `base`, such as the image of an atlas
+    /// page. By default, `relative` is joined to the directory containing `base`.
+    fn resolve(&self, base: &Path, relative: &Path) -> PathBuf {
+        base.parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(relative)
+    }
+}
+
+/// The real filesystem, using [`std::fs::read`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RealFs;
+
+impl SpineFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+}
+
+/// Files held in memory, keyed by path.
+///
+/// Paths are normalized, so `a/./b/../c.atlas` and `a/c.atlas` refer to the same file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryFs {
+    files: HashMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFs {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, replacing any file already at `path`.
+    pub fn insert<P: AsRef<Path>>(&mut self, path: P, data: impl Into<Vec<u8>>) {
+        self.files.insert(normalize(path.as_ref()), data.into());
+    }
+
+    #[must_use]
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P, data: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    /// Remove a file, returning its contents.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<Vec<u8>> {
+        self.files.remove(&normalize(path.as_ref()))
+    }
+
+    /// The paths of all files, in no particular order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+}
+
+impl SpineFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&normalize(path))
+            .cloned()
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn resolve(&self, base: &Path, relative: &Path) -> PathBuf {
+        normalize(
+            &base
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(relative),
+        )
+    }
+}
+
+/// Files in a zip archive. Requires the `zip` feature.
+///
+/// Paths are normalized and matched against the names of the entries in the archive, which always
+/// use `/` as a separator.
+#[cfg(feature = "zip")]
+#[derive(Debug)]
+pub struct ZipFs<R: Read + Seek> {
+    archive: Mutex<zip::ZipArchive<R>>,
+}
+
+#[cfg(feature = "zip")]
+impl ZipFs<File> {
+    /// Open a zip archive on the real filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened or is not a valid zip archive.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek> ZipFs<R> {
+    /// Read a zip archive, such as an in-memory [`std::io::Cursor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `reader` is not a valid zip archive.
+    pub fn new(reader: R) -> io::Result<Self> {
+        Ok(Self {
+            archive: Mutex::new(zip::ZipArchive::new(reader)?),
+        })
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek + Send> SpineFs for ZipFs<R> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let name = normalize(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut archive = self
+            .archive
+            .lock()
+            .map_err(|_| io::Error::other("zip archive lock poisoned"))?;
+        let mut file = archive.by_name(&name).map_err(|_| not_found(path))?;
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn resolve(&self, base: &Path, relative: &Path) -> PathBuf {
+        normalize(
+            &base
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(relative),
+        )
+    }
+}
+
+/// Remove `.` components and apply `..` components without touching the real filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let at_parent = matches!(
+                    normalized.components().next_back(),
+                    Some(Component::ParentDir)
+                );
+                if at_parent || !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("file not found: {}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        extension::{self, ExtensionCallbacks},
+        test::TestAsset,
+        Atlas, SkeletonBinary, SkeletonJson,
+    };
+
+    use super::*;
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(
+            normalize(Path::new("a/./b/../c.atlas")),
+            Path::new("a/c.atlas")
+        );
+        assert_eq!(normalize(Path::new("../a/b/../../c")), Path::new("../c"));
+        assert_eq!(normalize(Path::new("../../c")), Path::new("../../c"));
+        let fs = MemoryFs::new();
+        assert_eq!(
+            fs.resolve(Path::new("a/b/c.atlas"), Path::new("../images/c.png")),
+            Path::new("a/images/c.png")
+        );
+        assert_eq!(
+            RealFs.resolve(Path::new("c.atlas"), Path::new("c.png")),
+            Path::new("c.png")
+        );
+    }
+
+    /// Load an atlas and skeleton from memory, and check that page images are resolved relative
+    /// to the atlas.
+    #[test]
+    fn memory_fs() {
+        let asset = TestAsset::spineboy();
+        let fs = Arc::new(
+            MemoryFs::new()
+                .with_file("export/./spineboy.atlas", asset.atlas_data)
+                .with_file("export/spineboy.json", asset.json_data)
+                .with_file(
+                    "spineboy.skel",
+                    include_bytes!("../assets/spineboy/export/spineboy-pro.skel").as_slice(),
+                ),
+        );
+        assert!(fs.read(Path::new("missing.atlas")).is_err());
+        assert!(Atlas::new_from_fs(fs.as_ref(), "missing.atlas").is_err());
+
+        let texture_paths = Arc::new(Mutex::new(vec![]));
+        let recorded = texture_paths.clone();
+        let _scope = extension::scope(Arc::new(ExtensionCallbacks::new().with_create_texture_cb(
+            move |_, path| recorded.lock().unwrap().push(path.to_owned()),
+        )));
+        let atlas = Arc::new(Atlas::new_from_fs(fs.as_ref(), "export/spineboy.atlas").unwrap());
+        assert_eq!(*texture_paths.lock().unwrap(), vec!["export/spineboy.png"]);
+
+        SkeletonJson::new(atlas.clone())
+            .read_skeleton_data_fs(fs.as_ref(), "export/other/../spineboy.json")
+            .unwrap();
+        SkeletonBinary::new(atlas.clone())
+            .read_skeleton_data_fs(fs.as_ref(), "spineboy.skel")
+            .unwrap();
+
+        // Loading by path goes through the filesystem while it is in scope.
+        let _fs_scope = extension::scope(Arc::new(ExtensionCallbacks::new().with_fs(fs)));
+        SkeletonJson::new(atlas)
+            .read_skeleton_data_file("export/spineboy.json")
+            .unwrap();
+    }
+
+    /// A custom [`SpineFs::resolve`] is used for the path of each page image.
+    #[test]
+    fn custom_resolve() {
+        struct ImagesFs(MemoryFs);
+
+        impl SpineFs for ImagesFs {
+            fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+                self.0.read(path)
+            }
+
+            fn resolve(&self, base: &Path, relative: &Path) -> PathBuf {
+                if relative
+                    .extension()
+                    .is_some_and(|extension| extension == "png")
+                {
+                    Path::new("images").join(relative)
+                } else {
+                    self.0.resolve(base, relative)
+                }
+            }
+        }
+
+        let fs = ImagesFs(
+            MemoryFs::new().with_file("export/spineboy.atlas", TestAsset::spineboy().atlas_data),
+        );
+        let texture_paths = Arc::new(Mutex::new(vec![]));
+        let recorded = texture_paths.clone();
+        let _scope = extension::scope(Arc::new(ExtensionCallbacks::new().with_create_texture_cb(
+            move |_, path| recorded.lock().unwrap().push(path.to_owned()),
+        )));
+        let atlas = Atlas::new_from_fs(&fs, "export/spineboy.atlas").unwrap();
+        assert_eq!(*texture_paths.lock().unwrap(), vec!["images/spineboy.png"]);
+        assert!(atlas.pending_pages().is_empty());
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn zip_fs() {
+        use std::io::{Cursor, Write};
+
+        let asset = TestAsset::spineboy();
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        let options = zip::write::FileOptions::default();
+        writer
+            .start_file("spineboy/spineboy.atlas", options)
+            .unwrap();
+        writer.write_all(asset.atlas_data).unwrap();
+        writer
+            .start_file("spineboy/spineboy.json", options)
+            .unwrap();
+        writer.write_all(asset.json_data).unwrap();
+        let fs = ZipFs::new(writer.finish().unwrap()).unwrap();
+
+        assert!(fs.read(Path::new("spineboy/missing.json")).is_err());
+        let atlas = Arc::new(Atlas::new_from_fs(&fs, "spineboy/./spineboy.atlas").unwrap());
+        SkeletonJson::new(atlas)
+            .read_skeleton_data_fs(&fs, "spineboy/spineboy.json")
+            .unwrap();
+    }
+}
@@ -4,7 +4,8 @@
 //!
 //! To load a [`Skeleton`], see [`SkeletonJson`] or [`SkeletonBinary`].
 //!
-//! To load textures, see [`extension::set_create_texture_cb`].
+//! To load textures, see [`extension::set_create_texture_cb`], or [`extension::ExtensionCallbacks`]
+//! to use different callbacks per atlas.
 //!
 //! To set automatic mix durations (crossfading) between animations, see
 //! [`AnimationStateData`].
@@ -12,6 +13,52 @@
 //! To find and manage bones, see [`Bone`].
 //!
 //! To receive animation events, see [`AnimationState::set_listener`].
+//!
+//! # Threading
+//!
+//! [`Atlas`], [`SkeletonData`], and [`AnimationStateData`] are [`Send`] and [`Sync`], and are
+//! meant to be loaded once and shared between threads in an [`Arc`](`std::sync::Arc`), for
+//! example to create skeletons on worker threads. Only access the renderer objects of a shared
+//! atlas from the thread which owns its textures, unless they were provided with
+//! [`AtlasPage::set_texture`](`atlas::AtlasPage::set_texture`), which stores them atomically.
+//!
+//! [`Skeleton`], [`AnimationState`], and [`SkeletonController`](`controller::SkeletonController`)
+//! are [`Send`] but not [`Sync`]: the C runtime does no synchronization of its own, and some of
+//! their methods taking `&self` write to it. Each instance can be moved to a worker thread and
+//! updated there, for example with rayon, while other instances are updated elsewhere, but it
+//! cannot be shared between threads.
+//!
+//! ```compile_fail
+//! use rusty_spine::Skeleton;
+//!
+//! fn share(skeleton: &Skeleton) {
+//!     std::thread::scope(|scope| {
+//!         // Skeleton is not Sync, so it cannot be borrowed by another thread.
+//!         scope.spawn(|| skeleton.bones_count());
+//!     });
+//! }
+//! ```
+//!
+//! Callbacks stored by the runtime are called on whichever thread triggers them, so they must be
+//! [`Send`]: the listener set with [`AnimationState::set_listener`] is called on the thread
+//! updating the animation state, and the [`extension`] callbacks on the thread loading or dropping
+//! an atlas.
+//!
+//! ```compile_fail
+//! # #[path="./test.rs"]
+//! # mod test;
+//! use std::rc::Rc;
+//!
+//! # let (_, mut animation_state) = test::TestAsset::spineboy().instance();
+//! let events = Rc::new(std::cell::Cell::new(0));
+//! // Rc is not Send, so this listener is rejected.
+//! animation_state.set_listener(move |_, _| events.set(events.get() + 1));
+//! ```
+//!
+//! Textures often have to be created and used on a render thread. The renderer objects in
+//! renderables are raw pointers, which are not [`Send`], so update skeletons on worker threads
+//! and collect renderables on the render thread. To load atlases on a worker thread without
+//! creating textures there, see [`Atlas::new_deferred`].
 
 #![deny(
     clippy::missing_panics_doc,
@@ -29,13 +76,18 @@
     clippy::redundant_feature_names,
     clippy::semicolon_if_nothing_returned
 )]
-// https://github.com/rust-lang/rust-clippy/issues/11382
-#![allow(clippy::arc_with_non_send_sync)]
 
 #[macro_use]
 pub mod c_interface;
+pub mod blend_tree;
 pub mod c;
 pub mod extension;
+pub mod fs;
+pub mod memory;
+pub mod random;
+pub mod state_machine;
+#[cfg(feature = "texture_packer")]
+pub mod texture_packer;
 
 #[cfg(feature = "draw_functions")]
 pub mod controller;
@@ -45,11 +97,14 @@ pub mod draw;
 mod animation;
 mod animation_state;
 mod animation_state_data;
+mod atlas_builder;
 #[path = "atlas.rs"]
 mod atlas_mod;
+mod atlas_validator;
 mod attachment;
 mod attachment_loader;
 mod bone;
+mod bone_hierarchy;
 mod bounding_box_attachment;
 mod clipping_attachment;
 mod color;
@@ -69,6 +124,10 @@ mod skin;
 mod slot;
 #[cfg(not(feature = "spine38"))]
 mod texture_region;
+#[cfg(not(feature = "spine38"))]
+mod timeline;
+#[cfg(not(feature = "spine38"))]
+mod track_mask;
 
 pub use animation::*;
 pub use animation_state::*;
@@ -77,6 +136,7 @@ pub use atlas_mod::{atlas, Atlas};
 pub use attachment::*;
 pub use attachment_loader::*;
 pub use bone::*;
+pub use bone_hierarchy::*;
 pub use bounding_box_attachment::*;
 pub use clipping_attachment::*;
 pub use color::*;
@@ -96,6 +156,28 @@ pub use skin::*;
 pub use slot::*;
 #[cfg(not(feature = "spine38"))]
 pub use texture_region::*;
+#[cfg(not(feature = "spine38"))]
+pub use timeline::*;
+#[cfg(not(feature = "spine38"))]
+pub use track_mask::*;
 
 #[cfg(test)]
 pub mod test;
+
+// Enforce the threading model described in the crate documentation.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    const fn assert_send<T: Send>() {}
+    assert_send_sync::<Atlas>();
+    assert_send_sync::<SkeletonData>();
+    assert_send_sync::<AnimationStateData>();
+    assert_send_sync::<extension::ExtensionCallbacks>();
+    assert_send::<Skeleton>();
+    assert_send::<AnimationState>();
+    assert_send::<Skin>();
+    assert_send::<SkeletonClipping>();
+    assert_send::<SkeletonJson>();
+    assert_send::<SkeletonBinary>();
+    #[cfg(feature = "draw_functions")]
+    assert_send::<controller::SkeletonController>();
+};
@@ -0,0 +1,331 @@
+//! Memory usage of the Spine runtime.
+//!
+//! Every allocation made by the C runtime is counted, so the memory used by atlases, skeleton
+//! data, skeletons, and animation states can be budgeted and leaks can be caught in tests.
+//!
+//! ```
+//! # #[path="./test.rs"]
+//! # mod test;
+//! use rusty_spine::memory::{self, MemoryCategory};
+//!
+//! # let (skeleton, animation_state) = test::TestAsset::spineboy().instance();
+//! let stats = memory::category_stats(MemoryCategory::Skeleton);
+//! println!(
+//!     "skeletons use {} bytes in {} allocations",
+//!     stats.live_bytes, stats.live_allocations
+//! );
+//! ```
+//!
+//! Allocations are attributed to the [`MemoryCategory`] active on the allocating thread. The
+//! constructors of [`Atlas`](`crate::Atlas`), [`SkeletonData`](`crate::SkeletonData`) (through
+//! [`SkeletonJson`](`crate::SkeletonJson`) and [`SkeletonBinary`](`crate::SkeletonBinary`)),
+//! [`Skeleton`](`crate::Skeleton`), and [`AnimationState`](`crate::AnimationState`) set their own
+//! category, anything else is [`MemoryCategory::Other`] unless run inside [`with_category`].
+//!
+//! Only the allocator used without the `libc` feature keeps these counts. With the `libc` feature,
+//! [`is_tracked`] returns `false` and every count is zero.
+
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// What an allocation was made for, see [`category_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum MemoryCategory {
+    Atlas,
+    SkeletonData,
+    Skeleton,
+    AnimationState,
+    Other,
+}
+
+impl MemoryCategory {
+    pub const ALL: [Self; 5] = [
+        Self::Atlas,
+        Self::SkeletonData,
+        Self::Skeleton,
+        Self::AnimationState,
+        Self::Other,
+    ];
+
+    #[cfg(not(feature = "libc"))]
+    pub(crate) const fn from_index(index: usize) -> Self {
+        match index {
+            0 => Self::Atlas,
+            1 => Self::SkeletonData,
+            2 => Self::Skeleton,
+            3 => Self::AnimationState,
+            _ => Self::Other,
+        }
+    }
+
+    pub(crate) const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A snapshot of memory usage, see [`stats`] and [`category_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryStats {
+    /// Bytes currently allocated.
+    pub live_bytes: usize,
+    /// Number of allocations not yet freed.
+    pub live_allocations: usize,
+    /// The most bytes allocated at once since startup or the last call to [`reset_peak`].
+    pub peak_bytes: usize,
+    /// Number of allocations made since startup, including those already freed.
+    pub total_allocations: usize,
+}
+
+struct Counters {
+    live_bytes: AtomicUsize,
+    live_allocations: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    total_allocations: AtomicUsize,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            live_bytes: AtomicUsize::new(0),
+            live_allocations: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            total_allocations: AtomicUsize::new(0),
+        }
+    }
+
+    fn alloc(&self, size: usize) {
+        let live_bytes = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live_bytes, Ordering::Relaxed);
+        self.live_allocations.fetch_add(1, Ordering::Relaxed);
+        self.total_allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn free(&self, size: usize) {
+        self.live_bytes.fetch_sub(size, Ordering::Relaxed);
+        self.live_allocations.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn reset_peak(&self) {
+        self.peak_bytes
+            .store(self.live_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            live_bytes: self.live_bytes.load(Ordering::Relaxed),
+            live_allocations: self.live_allocations.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+            total_allocations: self.total_allocations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+static TOTAL: Counters = Counters::new();
+static CATEGORIES: [Counters; 5] = [
+    Counters::new(),
+    Counters::new(),
+    Counters::new(),
+    Counters::new(),
+    Counters::new(),
+];
+
+thread_local! {
+    static CATEGORY: Cell<MemoryCategory> = const { Cell::new(MemoryCategory::Other) };
+
+    /// Bytes and allocations made minus those freed on this thread, see [`assert_no_leaks`].
+    static THREAD_BALANCE: Cell<(isize, isize)> = const { Cell::new((0, 0)) };
+
+    /// Whether [`THREAD_BALANCE`] is left alone, see [`runtime_statics`].
+    static SKIP_BALANCE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Count an allocation of `size` bytes, returning the category it belongs to.
+#[cfg_attr(feature = "libc", allow(dead_code))]
+pub(crate) fn record_alloc(size: usize) -> MemoryCategory {
+    let category = CATEGORY.with(Cell::get);
+    record_alloc_in(size, category);
+    category
+}
+
+/// Count an allocation of `size` bytes in `category`, such as when reallocating.
+#[cfg_attr(feature = "libc", allow(dead_code))]
+pub(crate) fn record_alloc_in(size: usize, category: MemoryCategory) {
+    TOTAL.alloc(size);
+    CATEGORIES[category.index()].alloc(size);
+    if !SKIP_BALANCE.with(Cell::get) {
+        THREAD_BALANCE.with(|balance| {
+            let (bytes, allocations) = balance.get();
+            balance.set((bytes + size as isize, allocations + 1));
+        });
+    }
+}
+
+/// Count freeing an allocation of `size` bytes made in `category`.
+#[cfg_attr(feature = "libc", allow(dead_code))]
+pub(crate) fn record_free(size: usize, category: MemoryCategory) {
+    TOTAL.free(size);
+    CATEGORIES[category.index()].free(size);
+    if !SKIP_BALANCE.with(Cell::get) {
+        THREAD_BALANCE.with(|balance| {
+            let (bytes, allocations) = balance.get();
+            balance.set((bytes - size as isize, allocations - 1));
+        });
+    }
+}
+
+/// Run `f`, which initializes statics in the C runtime that are never freed, without counting
+/// them as leaks of the current thread.
+pub(crate) fn runtime_statics<T, F: FnOnce() -> T>(f: F) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SKIP_BALANCE.with(|skip| skip.set(self.0));
+        }
+    }
+    let _restore = Restore(SKIP_BALANCE.with(|skip| skip.replace(true)));
+    with_category(MemoryCategory::Other, f)
+}
+
+/// Whether allocations are counted. This is `false` with the `libc` feature, which allocates with
+/// the system `malloc`.
+#[must_use]
+pub const fn is_tracked() -> bool {
+    cfg!(not(feature = "libc"))
+}
+
+/// Memory usage of the whole runtime.
+#[must_use]
+pub fn stats() -> MemoryStats {
+    TOTAL.stats()
+}
+
+/// Memory usage of allocations made in `category`.
+#[must_use]
+pub fn category_stats(category: MemoryCategory) -> MemoryStats {
+    CATEGORIES[category.index()].stats()
+}
+
+/// Reset the peak usage of the runtime and every category to their current usage.
+pub fn reset_peak() {
+    TOTAL.reset_peak();
+    for counters in &CATEGORIES {
+        counters.reset_peak();
+    }
+}
+
+/// Attribute allocations made by `f` on this thread to `category`.
+///
+/// ```
+/// use rusty_spine::{
+///     memory::{self, MemoryCategory},
+///     Skin,
+/// };
+///
+/// let skin = memory::with_category(MemoryCategory::SkeletonData, || Skin::new("custom"));
+/// ```
+pub fn with_category<T, F: FnOnce() -> T>(category: MemoryCategory, f: F) -> T {
+    struct Restore(MemoryCategory);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CATEGORY.with(|current| current.set(self.0));
+        }
+    }
+    let _restore = Restore(CATEGORY.with(|current| current.replace(category)));
+    f()
+}
+
+/// Run `f`, then panic if the runtime memory allocated on this thread while it ran was not all
+/// freed by the time it returned. Meant for tests which create and drop Spine objects.
+///
+/// Only allocations and frees on the current thread are considered, so tests running in parallel
+/// do not affect each other. Objects created in `f` must be dropped on the same thread, and
+/// objects created before `f` must not be dropped inside it.
+///
+/// ```
+/// # #[path="./test.rs"]
+/// # mod test;
+/// use rusty_spine::memory;
+///
+/// memory::assert_no_leaks(|| {
+///     let (skeleton, animation_state) = test::TestAsset::spineboy().instance();
+/// });
+/// ```
+///
+/// # Panics
+///
+/// Panics if memory was leaked.
+#[track_caller]
+pub fn assert_no_leaks<T, F: FnOnce() -> T>(f: F) -> T {
+    let before = THREAD_BALANCE.with(Cell::get);
+    let result = f();
+    let after = THREAD_BALANCE.with(Cell::get);
+    assert!(
+        after == before || !is_tracked(),
+        "leaked {} bytes in {} allocations",
+        after.0 - before.0,
+        after.1 - before.1
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{test::TestAsset, AnimationState, AnimationStateData, Skeleton};
+
+    use super::*;
+
+    #[cfg(not(feature = "libc"))]
+    #[test]
+    fn memory_categories() {
+        let asset = TestAsset::spineboy();
+        let (skeleton_data, animation_state_data) = asset.instance_data();
+        let before = THREAD_BALANCE.with(Cell::get);
+        let skeleton = Skeleton::new(skeleton_data.clone());
+        let animation_state = AnimationState::new(animation_state_data.clone());
+        let after = THREAD_BALANCE.with(Cell::get);
+        assert!(after.0 > before.0);
+        assert!(category_stats(MemoryCategory::Skeleton).live_bytes > 0);
+        assert!(category_stats(MemoryCategory::SkeletonData).live_bytes > 0);
+        assert!(category_stats(MemoryCategory::Atlas).live_bytes > 0);
+        assert!(category_stats(MemoryCategory::AnimationState).live_allocations > 0);
+        let stats = stats();
+        assert!(stats.peak_bytes >= stats.live_bytes);
+        assert!(stats.total_allocations >= stats.live_allocations);
+        drop(skeleton);
+        drop(animation_state);
+        assert_eq!(THREAD_BALANCE.with(Cell::get), before);
+    }
+
+    #[test]
+    fn memory_no_leaks() {
+        assert_no_leaks(|| {
+            let asset = TestAsset::spineboy();
+            let (skeleton_data, animation_state_data) = asset.instance_data();
+            let mut skeleton = Skeleton::new(skeleton_data.clone());
+            let mut animation_state = AnimationState::new(animation_state_data);
+            animation_state
+                .set_animation_by_name(0, "run", true)
+                .unwrap();
+            animation_state.update(0.5);
+            animation_state.apply(&mut skeleton);
+            skeleton.update_world_transform();
+            let _animation_state_data = Arc::new(AnimationStateData::new(skeleton_data));
+        });
+    }
+
+    #[cfg(not(feature = "libc"))]
+    #[test]
+    #[should_panic(expected = "leaked")]
+    fn memory_leak_detected() {
+        assert_no_leaks(|| {
+            std::mem::forget(Skeleton::new(TestAsset::spineboy().instance_data().0));
+        });
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    atlas::AtlasRegion,
     c::{
         c_float, c_ushort, spAttachment, spMeshAttachment, spMeshAttachment_newLinkedMesh,
         spVertexAttachment,
@@ -67,6 +68,45 @@ impl MeshAttachment {
         spMeshAttachment_updateUVs(self.c_ptr());
     }
 
+    /// Point this attachment at a different atlas region, which may belong to another
+    /// [`Atlas`](`crate::Atlas`), and recompute its UVs. The change is seen by every skeleton
+    /// sharing this attachment.
+    ///
+    /// Linked meshes keep their own region, so each must be updated separately. Attachments with
+    /// a sequence take their region from the sequence instead.
+    ///
+    /// # Safety
+    ///
+    /// The atlas owning `region` must outlive this attachment, or be replaced before it is
+    /// dropped.
+    pub unsafe fn set_region(&mut self, region: &AtlasRegion) {
+        let c_region = region.c_ptr();
+        let attachment = self.c_ptr_mut();
+        attachment.rendererObject = c_region.cast();
+        #[cfg(not(feature = "spine38"))]
+        {
+            attachment.region = &mut (*c_region).super_0;
+            self.update_region();
+        }
+        #[cfg(feature = "spine38")]
+        {
+            let region = &*c_region;
+            attachment.regionU = region.u;
+            attachment.regionV = region.v;
+            attachment.regionU2 = region.u2;
+            attachment.regionV2 = region.v2;
+            attachment.regionRotate = region.rotate;
+            attachment.regionDegrees = region.degrees;
+            attachment.regionOffsetX = region.offsetX;
+            attachment.regionOffsetY = region.offsetY;
+            attachment.regionWidth = region.width;
+            attachment.regionHeight = region.height;
+            attachment.regionOriginalWidth = region.originalWidth;
+            attachment.regionOriginalHeight = region.originalHeight;
+            self.update_uvs();
+        }
+    }
+
     c_attachment_accessors!();
     c_vertex_attachment_accessors!();
     c_accessor_string!(path, path);
@@ -0,0 +1,68 @@
+//! Random numbers used by the Spine runtime, such as by the jitter and swirl vertex effects.
+//!
+//! Each thread has its own generator, which starts from the same seed, so the runtime behaves the
+//! same on every run unless seeded differently with [`set_random_seed`].
+//!
+//! ```
+//! use rusty_spine::random;
+//!
+//! // Replay a recorded session with the random numbers it used.
+//! random::set_random_seed(0x5eed);
+//! ```
+
+use std::cell::Cell;
+
+/// The largest value returned to the C runtime, matching the `RAND_MAX` it divides by.
+const RAND_MAX: u64 = 2_147_483_647;
+
+const DEFAULT_SEED: u64 = 1;
+
+thread_local! {
+    static STATE: Cell<u64> = const { Cell::new(DEFAULT_SEED) };
+}
+
+/// Seed the random number generator of the current thread. Seeding with the same value makes the
+/// runtime produce the same random numbers again.
+pub fn set_random_seed(seed: u64) {
+    STATE.with(|state| state.set(seed));
+}
+
+/// The next random number from the current thread's generator, in `0..=RAND_MAX`.
+///
+/// Uses `SplitMix64`, which is fast, has a single word of state, and is good enough for
+/// animation effects.
+pub(crate) fn next() -> i32 {
+    let mut z = STATE.with(|state| {
+        let next = state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(next);
+        next
+    });
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z % (RAND_MAX + 1)) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::c::_spMath_random;
+
+    use super::*;
+
+    #[test]
+    fn random_seed() {
+        let sample = || -> Vec<f32> {
+            (0..16)
+                .map(|_| unsafe { _spMath_random(-1., 1.) })
+                .collect()
+        };
+        set_random_seed(42);
+        let first = sample();
+        assert!(first.iter().all(|value| (-1. ..=1.).contains(value)));
+        assert!(first.windows(2).any(|values| values[0] != values[1]));
+        set_random_seed(42);
+        assert_eq!(sample(), first);
+        set_random_seed(43);
+        assert_ne!(sample(), first);
+    }
+}
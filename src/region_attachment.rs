@@ -1,10 +1,14 @@
 use crate::{
+    atlas::AtlasRegion,
     c::{c_float, spAttachment, spRegionAttachment, spRegionAttachment_computeWorldVertices},
-    c_interface::SyncPtr,
+    c_interface::{NewFromPtr, SyncPtr},
 };
 
 #[cfg(feature = "spine38")]
-use crate::{bone::Bone, c::spRegionAttachment_updateOffset};
+use crate::{
+    bone::Bone,
+    c::{spRegionAttachment_setUVs, spRegionAttachment_updateOffset},
+};
 
 #[cfg(not(feature = "spine38"))]
 use crate::{
@@ -18,7 +22,8 @@ use crate::{
 use mint::Vector2;
 
 /// Properties for updating [`RegionAttachment`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RegionProps {
     pub x: f32,
     pub y: f32,
@@ -39,14 +44,15 @@ pub struct RegionAttachment {
     c_region_attachment: SyncPtr<spRegionAttachment>,
 }
 
-impl RegionAttachment {
-    #[must_use]
-    pub const fn new_from_ptr(c_region_attachment: *mut spRegionAttachment) -> Self {
+impl NewFromPtr<spRegionAttachment> for RegionAttachment {
+    unsafe fn new_from_ptr(c_region_attachment: *mut spRegionAttachment) -> Self {
         Self {
             c_region_attachment: SyncPtr(c_region_attachment),
         }
     }
+}
 
+impl RegionAttachment {
     #[must_use]
     fn attachment(&self) -> &spAttachment {
         unsafe { &self.c_ptr_ref().super_0 }
@@ -117,6 +123,46 @@ impl RegionAttachment {
         spRegionAttachment_updateOffset(self.c_ptr());
     }
 
+    /// Point this attachment at a different atlas region, which may belong to another
+    /// [`Atlas`](`crate::Atlas`), and recompute its UVs. The change is seen by every skeleton
+    /// sharing this attachment.
+    ///
+    /// Attachments with a sequence take their region from the sequence instead.
+    ///
+    /// # Safety
+    ///
+    /// The atlas owning `region` must outlive this attachment, or be replaced before it is
+    /// dropped.
+    pub unsafe fn set_region(&mut self, region: &AtlasRegion) {
+        let c_region = region.c_ptr();
+        let attachment = self.c_ptr_mut();
+        attachment.rendererObject = c_region.cast();
+        #[cfg(not(feature = "spine38"))]
+        {
+            attachment.region = &mut (*c_region).super_0;
+            self.update_region();
+        }
+        #[cfg(feature = "spine38")]
+        {
+            let region = &*c_region;
+            spRegionAttachment_setUVs(
+                attachment,
+                region.u,
+                region.v,
+                region.u2,
+                region.v2,
+                region.rotate,
+            );
+            attachment.regionOffsetX = region.offsetX;
+            attachment.regionOffsetY = region.offsetY;
+            attachment.regionWidth = region.width;
+            attachment.regionHeight = region.height;
+            attachment.regionOriginalWidth = region.originalWidth;
+            attachment.regionOriginalHeight = region.originalHeight;
+            self.update_offset();
+        }
+    }
+
     c_attachment_accessors!();
     c_accessor_string_mut!(path, set_path, path);
     c_accessor_mut!(
@@ -1,16 +1,17 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, cell::Cell, marker::PhantomData, sync::Arc};
 
 use crate::{
-    bone::Bone,
+    bone::{Bone, BoneWorldTransform, WorldOverrideStage},
     c::{
-        spBone, spSkeleton, spSkeletonData, spSkeleton_create, spSkeleton_dispose,
+        spBone, spSkeleton, spSkeleton_create, spSkeleton_dispose,
         spSkeleton_getAttachmentForSlotIndex, spSkeleton_getAttachmentForSlotName,
         spSkeleton_setAttachment, spSkeleton_setBonesToSetupPose, spSkeleton_setSkin,
         spSkeleton_setSkinByName, spSkeleton_setSlotsToSetupPose, spSkeleton_setToSetupPose,
         spSkeleton_updateCache, spSkeleton_updateWorldTransform, spSkin, spSlot,
     },
-    c_interface::{to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
+    c_interface::{new_handle_id, to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     error::SpineError,
+    memory::{self, MemoryCategory},
     skeleton_data::SkeletonData,
     skin::Skin,
     slot::Slot,
@@ -33,8 +34,11 @@ use mint::Vector2;
 pub struct Skeleton {
     c_skeleton: SyncPtr<spSkeleton>,
     owns_memory: bool,
+    handle_id: u64,
+    y_down: bool,
     _skeleton_data: Arc<SkeletonData>,
     _skin: Option<Skin>, // keep-alive for user created skins
+    _not_sync: PhantomData<Cell<()>>,
 }
 
 impl Skeleton {
@@ -43,12 +47,17 @@ impl Skeleton {
     /// See [`SkeletonJson`] or [`SkeletonBinary`] for a complete example of loading a skeleton.
     #[must_use]
     pub fn new(skeleton_data: Arc<SkeletonData>) -> Self {
-        let c_skeleton = unsafe { spSkeleton_create(skeleton_data.c_ptr()) };
+        let c_skeleton = memory::with_category(MemoryCategory::Skeleton, || unsafe {
+            spSkeleton_create(skeleton_data.c_ptr())
+        });
         Self {
             c_skeleton: SyncPtr(c_skeleton),
             owns_memory: true,
+            handle_id: new_handle_id(),
+            y_down: false,
             _skeleton_data: skeleton_data,
             _skin: None, // keep alive user-created skins
+            _not_sync: PhantomData,
         }
     }
 
@@ -71,6 +80,71 @@ impl Skeleton {
         }
     }
 
+    /// Moves a bone to the given world transform, converting it into the bone's local transform,
+    /// and updates the world transform of the bones affected by it. Returns `false` if there is
+    /// no bone at the index.
+    ///
+    /// The world transforms must be up to date, such as after calling
+    /// [`update_world_transform`](`Self::update_world_transform`), since the bone's parent is used
+    /// to convert the transform. With [`WorldOverrideStage::BeforeConstraints`], constraints may
+    /// still move the bone afterward.
+    ///
+    /// ```
+    /// # #[path="./test.rs"]
+    /// # mod test;
+    /// use rusty_spine::{BoneWorldTransform, WorldOverrideStage};
+    ///
+    /// # let (mut skeleton, _) = test::TestAsset::spineboy().instance();
+    /// skeleton.update_world_transform();
+    /// let head = skeleton.find_bone_index("head").unwrap();
+    /// let mut transform = BoneWorldTransform::from_bone(&skeleton.bone_at_index(head).unwrap());
+    /// transform.rotation = 45.;
+    /// skeleton.set_bone_world_transform(head, &transform, WorldOverrideStage::AfterConstraints);
+    /// ```
+    pub fn set_bone_world_transform(
+        &mut self,
+        bone_index: usize,
+        transform: &BoneWorldTransform,
+        stage: WorldOverrideStage,
+    ) -> bool {
+        let Some(mut bone) = self.bone_at_index_mut(bone_index) else {
+            return false;
+        };
+        bone.set_world_transform(transform);
+        match stage {
+            WorldOverrideStage::BeforeConstraints => {
+                let bone = bone.as_mut();
+                bone.set_x(bone.applied_x());
+                bone.set_y(bone.applied_y());
+                bone.set_rotation(bone.applied_rotation());
+                bone.set_scale_x(bone.applied_scale_x());
+                bone.set_scale_y(bone.applied_scale_y());
+                bone.set_shear_x(bone.applied_shear_x());
+                bone.set_shear_y(bone.applied_shear_y());
+                self.update_world_transform();
+            }
+            WorldOverrideStage::AfterConstraints => {
+                let c_descendants: Vec<*mut spBone> = self
+                    .bone_descendants(bone_index)
+                    .map(|bone| bone.c_ptr())
+                    .collect();
+                for c_bone in c_descendants {
+                    let mut bone = unsafe { Bone::new_from_ptr(c_bone) };
+                    bone.update_world_transform_with(
+                        bone.applied_x(),
+                        bone.applied_y(),
+                        bone.applied_rotation(),
+                        bone.applied_scale_x(),
+                        bone.applied_scale_y(),
+                        bone.applied_shear_x(),
+                        bone.applied_shear_y(),
+                    );
+                }
+            }
+        }
+        true
+    }
+
     #[cfg(not(feature = "spine38"))]
     /// Temporarily sets the root bone as a child of the specified bone, then updates the world
     /// transform for each bone and applies all constraints.
@@ -86,6 +160,50 @@ impl Skeleton {
         spSkeleton_updateWorldTransformWith(self.c_ptr(), parent.c_ptr());
     }
 
+    /// Scales the entire skeleton on the Y axis.
+    ///
+    /// This does not include the flip from [`Skeleton::set_y_down`].
+    #[must_use]
+    pub fn scale_y(&self) -> f32 {
+        let scale_y = unsafe { self.c_ptr_ref().scaleY };
+        if self.y_down {
+            -scale_y
+        } else {
+            scale_y
+        }
+    }
+
+    /// Sets the scale the entire skeleton on the Y axis.
+    /// Bones that do not inherit scale are still affected by this property.
+    pub fn set_scale_y(&mut self, scale_y: f32) {
+        unsafe {
+            self.c_ptr_mut().scaleY = if self.y_down { -scale_y } else { scale_y };
+        }
+    }
+
+    /// Whether this skeleton is posed for a coordinate system where Y points down, such as
+    /// screen coordinates. See [`Skeleton::set_y_down`].
+    #[must_use]
+    pub const fn y_down(&self) -> bool {
+        self.y_down
+    }
+
+    /// Flip this skeleton on the Y axis when computing world transforms, for a coordinate system
+    /// where Y points down. Takes effect on the next call to [`Skeleton::update_world_transform`],
+    /// and is respected by everything using world transforms, including the drawers.
+    ///
+    /// Unlike [`Bone::set_y_down`], which applies to every skeleton in the process, this only
+    /// affects this skeleton, so skeletons in Y-up and Y-down spaces can be used side by side. If
+    /// both are set, they cancel out.
+    pub fn set_y_down(&mut self, y_down: bool) {
+        if y_down != self.y_down {
+            unsafe {
+                self.c_ptr_mut().scaleY = -self.c_ptr_ref().scaleY;
+            }
+            self.y_down = y_down;
+        }
+    }
+
     /// Sets the bones, constraints, slots, and draw order to their setup pose values.
     pub fn set_to_setup_pose(&mut self) {
         unsafe {
@@ -225,14 +343,23 @@ impl Skeleton {
         CTmpMut::new(self, unsafe { Bone::new_from_ptr(self.c_ptr_mut().root) })
     }
 
+    /// The index of the bone with the given name in [`bones`](`Self::bones`), or [`None`] if there
+    /// is no such bone. Takes constant time, see [`SkeletonData::find_bone_index`].
+    #[must_use]
+    pub fn find_bone_index(&self, name: &str) -> Option<usize> {
+        self._skeleton_data.find_bone_index(name)
+    }
+
     #[must_use]
     pub fn find_bone(&self, name: &str) -> Option<CTmpRef<Skeleton, Bone>> {
-        self.bones().find(|bone| bone.data().name() == name)
+        self.find_bone_index(name)
+            .and_then(|bone_index| self.bone_at_index(bone_index))
     }
 
     #[must_use]
     pub fn find_bone_mut(&mut self, name: &str) -> Option<CTmpMut<Skeleton, Bone>> {
-        self.bones_mut().find(|bone| bone.data().name() == name)
+        self.find_bone_index(name)
+            .and_then(|bone_index| self.bone_at_index_mut(bone_index))
     }
 
     #[must_use]
@@ -307,15 +434,24 @@ impl Skeleton {
 
     // TODO: iterators for ik, transform, path constraints
 
-    c_accessor_tmp_ptr_mut!(
-        /// The skeleton's setup pose data.
-        data,
-        /// The skeleton's mutable setup pose data.
-        data_mut,
-        data,
-        SkeletonData,
-        spSkeletonData
-    );
+    /// The skeleton's setup pose data.
+    #[must_use]
+    pub fn data(&self) -> CTmpRef<'_, Self, SkeletonData> {
+        CTmpRef::new(self, unsafe {
+            SkeletonData::new_borrowed(self.c_ptr_ref().data, self.data_handle_id())
+        })
+    }
+
+    /// The skeleton's mutable setup pose data.
+    #[must_use]
+    pub fn data_mut(&mut self) -> CTmpMut<'_, Self, SkeletonData> {
+        let c_data = unsafe { self.c_ptr_ref().data };
+        let data_handle_id = self.data_handle_id();
+        CTmpMut::new(self, unsafe {
+            SkeletonData::new_borrowed(c_data, data_handle_id)
+        })
+    }
+
     c_accessor_color_mut!(
         /// The color to tint all the skeleton's attachments.
         color,
@@ -362,15 +498,6 @@ impl Skeleton {
         scaleX,
         f32
     );
-    c_accessor_mut!(
-        /// Scales the entire skeleton on the Y axis.
-        scale_y,
-        /// Sets the scale the entire skeleton on the Y axis.
-        /// Bones that do not inherit scale are still affected by this property.
-        set_scale_y,
-        scaleY,
-        f32
-    );
     c_accessor_mut!(
         /// The skeleton X position, which is added to the root bone worldX position.
         x,
@@ -435,6 +562,17 @@ impl Skeleton {
         slots_count
     );
     c_accessor_tmp_ptr_optional_mut!(skin, skin_mut, skin, Skin, spSkin);
+    /// The id checked by handles to this object, so they are not valid for another object at the
+    /// same address.
+    pub(crate) const fn handle_id(&self) -> u64 {
+        self.handle_id
+    }
+
+    /// The handle id of this skeleton's [`SkeletonData`].
+    pub(crate) fn data_handle_id(&self) -> u64 {
+        self._skeleton_data.handle_id()
+    }
+
     c_ptr!(c_skeleton, spSkeleton);
 }
 
@@ -479,3 +617,176 @@ impl Drop for Skeleton {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test::TestAsset, BoneHandle, SlotHandle};
+
+    use super::*;
+
+    /// A Y-down skeleton must be the mirror image of a Y-up one, without affecting others.
+    #[test]
+    fn skeleton_y_down() {
+        let (skeleton_data, _) = TestAsset::spineboy().instance_data();
+        let mut y_up = Skeleton::new(skeleton_data.clone());
+        let mut y_down = Skeleton::new(skeleton_data);
+        y_down.set_y_down(true);
+        y_down.set_y_down(true);
+        assert!(y_down.y_down());
+        for skeleton in [&mut y_up, &mut y_down] {
+            skeleton.set_scale_x(2.);
+            skeleton.set_scale_y(3.);
+            skeleton.update_world_transform();
+        }
+        assert_eq!(y_down.scale_y(), 3.);
+        for (bone, flipped) in y_up.bones().zip(y_down.bones()) {
+            assert!((bone.world_x() - flipped.world_x()).abs() < 0.001);
+            assert!((bone.world_y() + flipped.world_y()).abs() < 0.001);
+        }
+        assert!(y_up.bones().any(|bone| bone.world_y().abs() > 1.));
+
+        y_down.set_y_down(false);
+        y_down.update_world_transform();
+        assert_eq!(y_down.scale_y(), 3.);
+        for (bone, unflipped) in y_up.bones().zip(y_down.bones()) {
+            assert!((bone.world_y() - unflipped.world_y()).abs() < 0.001);
+        }
+    }
+
+    /// Handles must not resolve against a new skeleton allocated at the address of the dropped
+    /// one.
+    #[test]
+    fn skeleton_handles_invalidate_drop() {
+        let (skeleton_data, _) = TestAsset::spineboy().instance_data();
+        let skeleton = Skeleton::new(skeleton_data.clone());
+        let bone_handle = skeleton.bone_root().handle();
+        let slot_handle = skeleton.slots().next().unwrap().handle();
+        assert!(bone_handle.get(&skeleton).is_some());
+        assert!(slot_handle.get(&skeleton).is_some());
+        let dropped_id = skeleton.handle_id();
+        drop(skeleton);
+
+        let mut skeleton = Skeleton::new(skeleton_data);
+        assert!(bone_handle.get(&skeleton).is_none());
+        assert!(slot_handle.get(&skeleton).is_none());
+
+        // Handles created from the dropped skeleton, as if this one was allocated at its address.
+        let bone_handle =
+            BoneHandle::new(skeleton.bone_root().c_ptr(), skeleton.c_ptr(), dropped_id);
+        let slot_handle = SlotHandle::new(
+            skeleton.slots().next().unwrap().c_ptr(),
+            skeleton.c_ptr(),
+            dropped_id,
+        );
+        assert!(bone_handle.get(&skeleton).is_none());
+        assert!(bone_handle.get_mut(&mut skeleton).is_none());
+        assert!(slot_handle.get(&skeleton).is_none());
+        assert!(skeleton.bone_root().handle().get(&skeleton).is_some());
+
+        // Handles with an unknown parent id never resolve.
+        let bone_handle = BoneHandle::new(skeleton.bone_root().c_ptr(), skeleton.c_ptr(), 0);
+        assert!(bone_handle.get(&skeleton).is_none());
+    }
+
+    /// Handles created from a bone or slot must only resolve if given the skeleton owning it.
+    #[test]
+    fn skeleton_handles_from_unborrowed() {
+        let (skeleton_data, _) = TestAsset::spineboy().instance_data();
+        let skeleton = Skeleton::new(skeleton_data.clone());
+        let other = Skeleton::new(skeleton_data);
+        let bone = skeleton.bone_root();
+        let slot = skeleton.slots().next().unwrap();
+        let child = bone.children().next().unwrap();
+        assert!(child.handle(&skeleton).get(&skeleton).is_some());
+        assert!((*slot).handle(&skeleton).get(&skeleton).is_some());
+        assert!((*bone).handle(&other).get(&skeleton).is_none());
+        assert!((*slot).handle(&other).get(&other).is_none());
+        let skin = skeleton.data().skins().next().unwrap().handle();
+        assert!(skin.get(&skeleton.data()).is_some());
+    }
+
+    fn assert_world_transform(bone: &Bone, transform: &BoneWorldTransform) {
+        let angle = |degrees: f32| (degrees + 540.).rem_euclid(360.) - 180.;
+        assert!((bone.world_x() - transform.x).abs() < 0.01);
+        assert!((bone.world_y() - transform.y).abs() < 0.01);
+        assert!(angle(bone.world_rotation_x() - transform.rotation).abs() < 0.01);
+        assert!((bone.world_scale_x() - transform.scale_x).abs() < 0.001);
+        assert!((bone.world_scale_y() - transform.scale_y).abs() < 0.001);
+    }
+
+    #[test]
+    fn skeleton_bone_world_transform_after_constraints() {
+        let (mut skeleton, _) = TestAsset::spineboy().instance();
+        skeleton.update_world_transform();
+        let bracer = skeleton.find_bone_index("rear-bracer").unwrap();
+        let gun = skeleton.find_bone_index("gun").unwrap();
+        let head = skeleton.find_bone_index("head").unwrap();
+        let gun_local = |skeleton: &Skeleton| {
+            let gun = skeleton.bone_at_index(gun).unwrap();
+            let (x, y) = skeleton
+                .bone_at_index(bracer)
+                .unwrap()
+                .world_to_local(gun.world_x(), gun.world_y());
+            (x, y)
+        };
+        let gun_before = gun_local(&skeleton);
+        let head_before = BoneWorldTransform::from_bone(&skeleton.bone_at_index(head).unwrap());
+
+        let transform = BoneWorldTransform {
+            x: 120.,
+            y: 80.,
+            rotation: 30.,
+            scale_x: 1.5,
+            scale_y: 0.5,
+        };
+        assert!(skeleton.set_bone_world_transform(
+            bracer,
+            &transform,
+            WorldOverrideStage::AfterConstraints
+        ));
+        assert_world_transform(&skeleton.bone_at_index(bracer).unwrap(), &transform);
+        let gun_after = gun_local(&skeleton);
+        assert!((gun_after.0 - gun_before.0).abs() < 0.01);
+        assert!((gun_after.1 - gun_before.1).abs() < 0.01);
+        assert_world_transform(&skeleton.bone_at_index(head).unwrap(), &head_before);
+        assert!(!skeleton.set_bone_world_transform(
+            skeleton.bones_count(),
+            &transform,
+            WorldOverrideStage::AfterConstraints
+        ));
+    }
+
+    #[test]
+    fn skeleton_bone_world_transform_before_constraints() {
+        let (mut skeleton, _) = TestAsset::spineboy().instance();
+        skeleton.set_y_down(true);
+        skeleton.update_world_transform();
+        let leg_target = skeleton.find_bone_index("front-leg-target").unwrap();
+        let thigh = skeleton.find_bone_index("front-thigh").unwrap();
+        let thigh_rotation = skeleton.bone_at_index(thigh).unwrap().world_rotation_x();
+
+        let mut transform =
+            BoneWorldTransform::from_bone(&skeleton.bone_at_index(leg_target).unwrap());
+        transform.x = -300.;
+        transform.y = -300.;
+        skeleton.set_bone_world_transform(
+            leg_target,
+            &transform,
+            WorldOverrideStage::BeforeConstraints,
+        );
+        assert_world_transform(&skeleton.bone_at_index(leg_target).unwrap(), &transform);
+        assert!(
+            (skeleton.bone_at_index(thigh).unwrap().world_rotation_x() - thigh_rotation).abs() > 1.
+        );
+
+        let transform = BoneWorldTransform {
+            x: 10.,
+            y: 20.,
+            rotation: -45.,
+            scale_x: 2.,
+            scale_y: 2.,
+        };
+        skeleton.set_bone_world_transform(0, &transform, WorldOverrideStage::BeforeConstraints);
+        assert_world_transform(&skeleton.bone_at_index(0).unwrap(), &transform);
+    }
+}
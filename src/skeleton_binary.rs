@@ -5,12 +5,16 @@ use std::{
 };
 
 use crate::{
+    attachment_loader::MultiAtlasAttachmentLoader,
     c::{
-        c_uchar, spSkeletonBinary, spSkeletonBinary_create, spSkeletonBinary_dispose,
-        spSkeletonBinary_readSkeletonData, spSkeletonBinary_readSkeletonDataFile,
+        c_uchar, spSkeletonBinary, spSkeletonBinary_create, spSkeletonBinary_createWithLoader,
+        spSkeletonBinary_dispose, spSkeletonBinary_readSkeletonData,
+        spSkeletonBinary_readSkeletonDataFile,
     },
     c_interface::{from_c_str, SyncPtr},
     error::SpineError,
+    fs::SpineFs,
+    memory::{self, MemoryCategory},
     skeleton_data::SkeletonData,
     Atlas,
 };
@@ -22,7 +26,8 @@ use crate::{
 pub struct SkeletonBinary {
     c_skeleton_binary: SyncPtr<spSkeletonBinary>,
     owns_memory: bool,
-    atlas: Option<Arc<Atlas>>,
+    atlases: Vec<Arc<Atlas>>,
+    attachment_loader: Option<SyncPtr<MultiAtlasAttachmentLoader>>,
 }
 
 impl SkeletonBinary {
@@ -49,7 +54,41 @@ impl SkeletonBinary {
         Self {
             c_skeleton_binary: SyncPtr(c_skeleton_binary),
             owns_memory: true,
-            atlas: Some(atlas),
+            atlases: vec![atlas],
+            attachment_loader: None,
+        }
+    }
+
+    /// Create a new Binary loader which looks up regions in each of `atlases` in order, using the
+    /// first atlas containing the region. Every atlas is kept alive by the loaded
+    /// [`SkeletonData`].
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use rusty_spine::{Atlas, SpineError, SkeletonData, SkeletonBinary};
+    ///
+    /// fn load_skeleton_data() -> Result<SkeletonData, SpineError> {
+    ///     let character_atlas = Arc::new(Atlas::new_from_file("character.atlas")?);
+    ///     let equipment_atlas = Arc::new(Atlas::new_from_file("equipment.atlas")?);
+    ///     let skeleton_binary = SkeletonBinary::new_with_atlases(vec![character_atlas, equipment_atlas]);
+    ///     skeleton_binary.read_skeleton_data_file("character.skel")
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `atlases` is empty.
+    #[must_use]
+    pub fn new_with_atlases(atlases: Vec<Arc<Atlas>>) -> Self {
+        assert!(!atlases.is_empty(), "at least one atlas is required");
+        let attachment_loader = MultiAtlasAttachmentLoader::new(&atlases);
+        let c_skeleton_binary =
+            unsafe { spSkeletonBinary_createWithLoader(attachment_loader.0.cast()) };
+        Self {
+            c_skeleton_binary: SyncPtr(c_skeleton_binary),
+            owns_memory: true,
+            atlases,
+            attachment_loader: Some(attachment_loader),
         }
     }
 
@@ -60,15 +99,15 @@ impl SkeletonBinary {
     ///
     /// Returns [`SpineError::ParsingFailed`] if parsing of the binary data failed.
     pub fn read_skeleton_data(&self, data: &[u8]) -> Result<SkeletonData, SpineError> {
-        let c_skeleton_data = unsafe {
+        let c_skeleton_data = memory::with_category(MemoryCategory::SkeletonData, || unsafe {
             spSkeletonBinary_readSkeletonData(
                 self.c_skeleton_binary.0,
                 data.as_ptr().cast::<c_uchar>(),
                 data.len() as i32,
             )
-        };
+        });
         if !c_skeleton_data.is_null() {
-            Ok(SkeletonData::new(c_skeleton_data, self.atlas.clone()))
+            Ok(SkeletonData::new(c_skeleton_data, self.atlases.clone()))
         } else {
             let c_error = unsafe { CStr::from_ptr((*self.c_skeleton_binary.0).error) };
             Err(SpineError::new_from_spine(from_c_str(c_error)))
@@ -91,17 +130,35 @@ impl SkeletonBinary {
             return Err(SpineError::PathNotUtf8);
         };
         let c_path = CString::new(path_str)?;
-        let c_skeleton_data = unsafe {
+        let c_skeleton_data = memory::with_category(MemoryCategory::SkeletonData, || unsafe {
             spSkeletonBinary_readSkeletonDataFile(self.c_skeleton_binary.0, c_path.as_ptr())
-        };
+        });
         if !c_skeleton_data.is_null() {
-            Ok(SkeletonData::new(c_skeleton_data, self.atlas.clone()))
+            Ok(SkeletonData::new(c_skeleton_data, self.atlases.clone()))
         } else {
             let c_error = unsafe { CStr::from_ptr((*self.c_skeleton_binary.0).error) };
             Err(SpineError::new_from_spine(from_c_str(c_error)))
         }
     }
 
+    /// Read the Spine skeleton binary data from a file in `fs`, see [`SpineFs`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::FailedToReadFile`] if the file could not be read. Returns any error
+    /// from [`SkeletonBinary::read_skeleton_data`].
+    pub fn read_skeleton_data_fs<F: SpineFs + ?Sized, P: AsRef<Path>>(
+        &self,
+        fs: &F,
+        path: P,
+    ) -> Result<SkeletonData, SpineError> {
+        let path = path.as_ref();
+        let data = fs
+            .read(path)
+            .map_err(|_| SpineError::new_failed_to_read_file(path))?;
+        self.read_skeleton_data(&data)
+    }
+
     c_accessor_mut!(
         /// Scales bone positions, image sizes, and translations as they are loaded. This allows
         /// different size images to be used at runtime than were used in Spine.
@@ -124,5 +181,8 @@ impl Drop for SkeletonBinary {
                 spSkeletonBinary_dispose(self.c_skeleton_binary.0);
             }
         }
+        if let Some(attachment_loader) = self.attachment_loader.take() {
+            drop(unsafe { Box::from_raw(attachment_loader.0) });
+        }
     }
 }
@@ -1,10 +1,13 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     animation::Animation,
     bone::BoneData,
     c::{spAnimation, spBoneData, spSkeletonData, spSkeletonData_dispose, spSkin, spSlotData},
-    c_interface::{CTmpRef, NewFromPtr, SyncPtr},
+    c_interface::{new_handle_id, CTmpRef, NewFromPtr, SyncPtr},
     skin::Skin,
     slot::SlotData,
     Atlas,
@@ -20,37 +23,81 @@ use mint::Vector2;
 pub struct SkeletonData {
     c_skeleton_data: SyncPtr<spSkeletonData>,
     owns_memory: bool,
+    handle_id: u64,
     // TODO: this atlas arc is kind of a hack
     // skeleton data should keep a reference to data it requires
     // but that will not be an atlas if a custom attachment loader is used
-    _atlas: Option<Arc<Atlas>>,
+    atlases: Vec<Arc<Atlas>>,
+    bone_indices: OnceLock<HashMap<String, usize>>,
 }
 
 impl NewFromPtr<spSkeletonData> for SkeletonData {
     unsafe fn new_from_ptr(c_skeleton_data: *mut spSkeletonData) -> Self {
-        Self {
-            c_skeleton_data: SyncPtr(c_skeleton_data),
-            owns_memory: false,
-            _atlas: None,
-        }
+        Self::new_borrowed(c_skeleton_data, 0)
     }
 }
 
 impl SkeletonData {
-    pub(crate) const fn new(
+    /// Wrap skeleton data owned by another [`SkeletonData`], checking handles against its id.
+    pub(crate) const unsafe fn new_borrowed(
         c_skeleton_data: *mut spSkeletonData,
-        atlas: Option<Arc<Atlas>>,
+        handle_id: u64,
     ) -> Self {
+        Self {
+            c_skeleton_data: SyncPtr(c_skeleton_data),
+            owns_memory: false,
+            handle_id,
+            atlases: vec![],
+            bone_indices: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn new(c_skeleton_data: *mut spSkeletonData, atlases: Vec<Arc<Atlas>>) -> Self {
         Self {
             c_skeleton_data: SyncPtr(c_skeleton_data),
             owns_memory: true,
-            _atlas: atlas,
+            handle_id: new_handle_id(),
+            atlases,
+            bone_indices: OnceLock::new(),
+        }
+    }
+
+    /// The atlases this skeleton data was loaded with, in lookup order. Empty if not loaded with
+    /// [`SkeletonJson`](`crate::SkeletonJson`) or [`SkeletonBinary`](`crate::SkeletonBinary`).
+    #[must_use]
+    pub fn atlases(&self) -> &[Arc<Atlas>] {
+        &self.atlases
+    }
+
+    /// The index of the bone with the given name in [`bones`](`Self::bones`), or [`None`] if there
+    /// is no such bone.
+    ///
+    /// The first lookup builds a map from bone names to indices, after which lookups take constant
+    /// time. Skeleton data acquired from a pointer, such as through
+    /// [`Skeleton::data`](`crate::Skeleton::data`), doesn't keep the map between calls and
+    /// searches the bones instead.
+    #[must_use]
+    pub fn find_bone_index(&self, name: &str) -> Option<usize> {
+        if self.owns_memory {
+            self.bone_indices
+                .get_or_init(|| {
+                    self.bones()
+                        .map(|bone| (bone.name().to_owned(), bone.index()))
+                        .collect()
+                })
+                .get(name)
+                .copied()
+        } else {
+            self.bones()
+                .find(|bone| bone.name() == name)
+                .map(|bone| bone.index())
         }
     }
 
     #[must_use]
     pub fn find_bone(&self, name: &str) -> Option<CTmpRef<SkeletonData, BoneData>> {
-        self.bones().find(|bone| bone.name() == name)
+        self.find_bone_index(name)
+            .and_then(|bone_index| self.bone_at_index(bone_index))
     }
 
     #[must_use]
@@ -165,6 +212,12 @@ impl SkeletonData {
         animations,
         animations_count
     );
+    /// The id checked by handles to this object, so they are not valid for another object at the
+    /// same address.
+    pub(crate) const fn handle_id(&self) -> u64 {
+        self.handle_id
+    }
+
     c_ptr!(c_skeleton_data, spSkeletonData);
 
     // TODO: accessors and methods for the arrays in spSkeletonData
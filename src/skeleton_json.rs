@@ -5,12 +5,16 @@ use std::{
 };
 
 use crate::{
+    attachment_loader::MultiAtlasAttachmentLoader,
     c::{
-        spSkeletonJson, spSkeletonJson_create, spSkeletonJson_dispose,
-        spSkeletonJson_readSkeletonData, spSkeletonJson_readSkeletonDataFile,
+        spSkeletonJson, spSkeletonJson_create, spSkeletonJson_createWithLoader,
+        spSkeletonJson_dispose, spSkeletonJson_readSkeletonData,
+        spSkeletonJson_readSkeletonDataFile,
     },
     c_interface::{from_c_str, SyncPtr},
     error::SpineError,
+    fs::SpineFs,
+    memory::{self, MemoryCategory},
     skeleton_data::SkeletonData,
     Atlas,
 };
@@ -22,7 +26,8 @@ use crate::{
 pub struct SkeletonJson {
     c_skeleton_json: SyncPtr<spSkeletonJson>,
     owns_memory: bool,
-    atlas: Option<Arc<Atlas>>,
+    atlases: Vec<Arc<Atlas>>,
+    attachment_loader: Option<SyncPtr<MultiAtlasAttachmentLoader>>,
 }
 
 impl SkeletonJson {
@@ -49,7 +54,41 @@ impl SkeletonJson {
         Self {
             c_skeleton_json: SyncPtr(c_skeleton_json),
             owns_memory: true,
-            atlas: Some(atlas),
+            atlases: vec![atlas],
+            attachment_loader: None,
+        }
+    }
+
+    /// Create a new JSON loader which looks up regions in each of `atlases` in order, using the
+    /// first atlas containing the region. Every atlas is kept alive by the loaded
+    /// [`SkeletonData`].
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use rusty_spine::{Atlas, SpineError, SkeletonData, SkeletonJson};
+    ///
+    /// fn load_skeleton_data() -> Result<SkeletonData, SpineError> {
+    ///     let character_atlas = Arc::new(Atlas::new_from_file("character.atlas")?);
+    ///     let equipment_atlas = Arc::new(Atlas::new_from_file("equipment.atlas")?);
+    ///     let skeleton_json = SkeletonJson::new_with_atlases(vec![character_atlas, equipment_atlas]);
+    ///     skeleton_json.read_skeleton_data_file("character.json")
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `atlases` is empty.
+    #[must_use]
+    pub fn new_with_atlases(atlases: Vec<Arc<Atlas>>) -> Self {
+        assert!(!atlases.is_empty(), "at least one atlas is required");
+        let attachment_loader = MultiAtlasAttachmentLoader::new(&atlases);
+        let c_skeleton_json =
+            unsafe { spSkeletonJson_createWithLoader(attachment_loader.0.cast()) };
+        Self {
+            c_skeleton_json: SyncPtr(c_skeleton_json),
+            owns_memory: true,
+            atlases,
+            attachment_loader: Some(attachment_loader),
         }
     }
 
@@ -60,10 +99,11 @@ impl SkeletonJson {
     /// Returns [`SpineError::ParsingFailed`] if parsing of the json data failed.
     pub fn read_skeleton_data(&self, json: &[u8]) -> Result<SkeletonData, SpineError> {
         let c_json = CString::new(json)?;
-        let c_skeleton_data =
-            unsafe { spSkeletonJson_readSkeletonData(self.c_skeleton_json.0, c_json.as_ptr()) };
+        let c_skeleton_data = memory::with_category(MemoryCategory::SkeletonData, || unsafe {
+            spSkeletonJson_readSkeletonData(self.c_skeleton_json.0, c_json.as_ptr())
+        });
         if !c_skeleton_data.is_null() {
-            Ok(SkeletonData::new(c_skeleton_data, self.atlas.clone()))
+            Ok(SkeletonData::new(c_skeleton_data, self.atlases.clone()))
         } else {
             let c_error = unsafe { CStr::from_ptr((*self.c_skeleton_json.0).error) };
             Err(SpineError::new_from_spine(from_c_str(c_error)))
@@ -85,16 +125,35 @@ impl SkeletonJson {
             return Err(SpineError::PathNotUtf8);
         };
         let c_path = CString::new(path_str)?;
-        let c_skeleton_data =
-            unsafe { spSkeletonJson_readSkeletonDataFile(self.c_skeleton_json.0, c_path.as_ptr()) };
+        let c_skeleton_data = memory::with_category(MemoryCategory::SkeletonData, || unsafe {
+            spSkeletonJson_readSkeletonDataFile(self.c_skeleton_json.0, c_path.as_ptr())
+        });
         if !c_skeleton_data.is_null() {
-            Ok(SkeletonData::new(c_skeleton_data, self.atlas.clone()))
+            Ok(SkeletonData::new(c_skeleton_data, self.atlases.clone()))
         } else {
             let c_error = unsafe { CStr::from_ptr((*self.c_skeleton_json.0).error) };
             Err(SpineError::new_from_spine(from_c_str(c_error)))
         }
     }
 
+    /// Read the Spine skeleton json data from a file in `fs`, see [`SpineFs`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::FailedToReadFile`] if the file could not be read. Returns any error
+    /// from [`SkeletonJson::read_skeleton_data`].
+    pub fn read_skeleton_data_fs<F: SpineFs + ?Sized, P: AsRef<Path>>(
+        &self,
+        fs: &F,
+        path: P,
+    ) -> Result<SkeletonData, SpineError> {
+        let path = path.as_ref();
+        let data = fs
+            .read(path)
+            .map_err(|_| SpineError::new_failed_to_read_file(path))?;
+        self.read_skeleton_data(&data)
+    }
+
     c_accessor_mut!(
         /// Scales bone positions, image sizes, and translations as they are loaded. This allows
         /// different size images to be used at runtime than were used in Spine.
@@ -117,5 +176,85 @@ impl Drop for SkeletonJson {
                 spSkeletonJson_dispose(self.c_skeleton_json.0);
             }
         }
+        if let Some(attachment_loader) = self.attachment_loader.take() {
+            drop(unsafe { Box::from_raw(attachment_loader.0) });
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "spine38"))]
+mod tests {
+    use crate::{
+        atlas::{AtlasBuilder, AtlasPageDesc},
+        memory,
+        test::TestAsset,
+        SkeletonBinary,
+    };
+
+    use super::*;
+
+    /// Split the atlas of `asset` in two, alternating regions between the atlases.
+    fn split_atlas(asset: &TestAsset) -> [Arc<Atlas>; 2] {
+        let page = AtlasBuilder::from_atlas(&asset.atlas()).pages.remove(0);
+        let mut pages = [
+            AtlasPageDesc::new("first.png", page.width, page.height),
+            AtlasPageDesc::new("second.png", page.width, page.height),
+        ];
+        for (index, region) in page.regions.into_iter().enumerate() {
+            pages[index % 2].regions.push(region);
+        }
+        pages.map(|page| Arc::new(AtlasBuilder::new().with_page(page).build("").unwrap()))
+    }
+
+    /// Split spineboy's atlas in two, and load it with regions falling through to the second.
+    #[test]
+    fn multiple_atlases() {
+        let asset = TestAsset::spineboy();
+        let [first, second] = split_atlas(asset);
+
+        assert!(SkeletonJson::new(first.clone())
+            .read_skeleton_data(asset.json_data)
+            .is_err());
+        let skeleton_data = SkeletonJson::new_with_atlases(vec![first.clone(), second.clone()])
+            .read_skeleton_data(asset.json_data)
+            .unwrap();
+        assert_eq!(skeleton_data.atlases().len(), 2);
+        let skeleton_binary = SkeletonBinary::new_with_atlases(vec![second.clone(), first.clone()]);
+        skeleton_binary
+            .read_skeleton_data_file("assets/spineboy/export/spineboy-pro.skel")
+            .unwrap();
+
+        // The skeleton data keeps both atlases alive.
+        drop((first, second, skeleton_binary));
+        let mut skeleton = crate::Skeleton::new(Arc::new(skeleton_data));
+        skeleton.set_to_setup_pose();
+        let pages: Vec<String> = skeleton
+            .slots()
+            .filter_map(|slot| {
+                let attachment = slot.attachment()?;
+                let region_attachment = attachment.as_region()?;
+                let mut renderer_object = region_attachment.renderer_object();
+                let region = unsafe { renderer_object.get_atlas_region() }?;
+                Some(region.page().name().to_owned())
+            })
+            .collect();
+        assert!(pages.iter().any(|page| page == "first.png"));
+        assert!(pages.iter().any(|page| page == "second.png"));
+    }
+
+    /// Attachments which are not in the first atlas must not leak.
+    #[test]
+    fn multiple_atlases_no_leaks() {
+        memory::assert_no_leaks(|| {
+            let asset = TestAsset::spineboy();
+            let atlases = split_atlas(asset);
+            SkeletonJson::new_with_atlases(atlases.to_vec())
+                .read_skeleton_data(asset.json_data)
+                .unwrap();
+            SkeletonBinary::new_with_atlases(atlases.to_vec())
+                .read_skeleton_data_file("assets/spineboy/export/spineboy-pro.skel")
+                .unwrap();
+        });
     }
 }
@@ -1,10 +1,12 @@
 use crate::{
+    atlas::AtlasRegion,
     c::{
-        spSkeletonData, spSkin, spSkin_addSkin, spSkin_copySkin, spSkin_create, spSkin_dispose,
-        spSkin_getAttachments,
+        spAtlasRegion, spSkeletonData, spSkin, spSkin_addSkin, spSkin_copySkin, spSkin_create,
+        spSkin_dispose, spSkin_getAttachments,
     },
     c_interface::{to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
-    Attachment, Skeleton, SkeletonData,
+    error::SpineError,
+    Atlas, Attachment, Skeleton, SkeletonData,
 };
 
 /// A container for attachments which can be applied to a skeleton.
@@ -71,6 +73,106 @@ impl Skin {
         attachments
     }
 
+    /// Point the region and mesh attachments in this skin at regions in `atlas`, chosen by
+    /// `mapping` from the name of each attachment's current region. Attachments for which
+    /// `mapping` returns [`None`] are left unchanged. Returns the number of attachments remapped.
+    ///
+    /// Mapping every name to itself swaps the textures for an atlas with the same region names,
+    /// without reloading the [`SkeletonData`]. Attachments are shared with the skeleton data, so
+    /// to remap a single skeleton, remap a [`Skin::clone`] and set it on that skeleton instead.
+    ///
+    /// ```
+    /// # #[path="./test.rs"]
+    /// # mod test;
+    /// # let (skeleton, _) = test::TestAsset::spineboy().instance();
+    /// # let atlas = test::TestAsset::spineboy().atlas();
+    /// let mut skin = skeleton.data().default_skin().clone();
+    /// let remapped = unsafe { skin.remap_regions(&atlas, |name| Some(name.to_owned())) }?;
+    /// # assert!(remapped > 0);
+    /// # Ok::<(), rusty_spine::SpineError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if a mapped region does not exist in `atlas`, in which
+    /// case no attachments are changed.
+    ///
+    /// # Safety
+    ///
+    /// The attachments must have been loaded by the atlas attachment loader, and `atlas` must
+    /// outlive every skeleton using them. See [`RegionAttachment::set_region`](`crate::RegionAttachment::set_region`).
+    pub unsafe fn remap_regions<F: FnMut(&str) -> Option<String>>(
+        &mut self,
+        atlas: &Atlas,
+        mut mapping: F,
+    ) -> Result<usize, SpineError> {
+        self.remap_regions_with(|current| {
+            let Some(name) = mapping(current.name()) else {
+                return Ok(None);
+            };
+            let Some(region) = atlas.find_region(&name) else {
+                return Err(SpineError::new_not_found("AtlasRegion", &name));
+            };
+            Ok(Some(region.c_ptr()))
+        })
+    }
+
+    /// Point the region and mesh attachments in this skin at the region `find` returns for each
+    /// attachment's current region, see [`Skin::remap_regions`]. If `find` fails, no attachments
+    /// are changed.
+    ///
+    /// # Safety
+    ///
+    /// See [`Skin::remap_regions`].
+    pub(crate) unsafe fn remap_regions_with<
+        F: FnMut(&AtlasRegion) -> Result<Option<*mut spAtlasRegion>, SpineError>,
+    >(
+        &mut self,
+        mut find: F,
+    ) -> Result<usize, SpineError> {
+        let mut remaps = vec![];
+        for (attachment, current) in self.atlas_region_attachments() {
+            if let Some(c_region) = find(&current)? {
+                remaps.push((attachment, c_region));
+            }
+        }
+        for (attachment, c_region) in &mut remaps {
+            let region = AtlasRegion::new_from_ptr(*c_region);
+            if let Some(mut region_attachment) = attachment.as_region_mut() {
+                region_attachment.set_region(&region);
+            } else if let Some(mut mesh) = attachment.as_mesh_mut() {
+                mesh.set_region(&region);
+            }
+        }
+        Ok(remaps.len())
+    }
+
+    /// The region and mesh attachments in this skin, paired with the atlas region each one draws.
+    ///
+    /// # Safety
+    ///
+    /// The attachments must have been loaded by the atlas attachment loader.
+    pub(crate) unsafe fn atlas_region_attachments(&self) -> Vec<(Attachment, AtlasRegion)> {
+        let mut attachments = vec![];
+        for entry in self.attachments() {
+            let attachment = entry.attachment;
+            let renderer_object = if let Some(region) = attachment.as_region() {
+                region.c_ptr_ref().rendererObject
+            } else if let Some(mesh) = attachment.as_mesh() {
+                mesh.c_ptr_ref().rendererObject
+            } else {
+                continue;
+            };
+            if !renderer_object.is_null() {
+                attachments.push((
+                    attachment,
+                    AtlasRegion::new_from_ptr(renderer_object.cast()),
+                ));
+            }
+        }
+        attachments
+    }
+
     c_accessor_string!(name, name);
     c_ptr!(c_skin, spSkin);
     // TODO: accessors
@@ -123,28 +225,36 @@ c_handle_decl!(
 impl<'a> CTmpRef<'a, SkeletonData, Skin> {
     #[must_use]
     pub fn handle(&self) -> SkinHandle {
-        SkinHandle::new(self.c_ptr(), self.parent.c_ptr())
+        SkinHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
     }
 }
 
 impl<'a> CTmpMut<'a, SkeletonData, Skin> {
     #[must_use]
     pub fn handle(&self) -> SkinHandle {
-        SkinHandle::new(self.c_ptr(), self.parent.c_ptr())
+        SkinHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
     }
 }
 
 impl<'a> CTmpRef<'a, Skeleton, Skin> {
     #[must_use]
     pub fn handle(&self) -> SkinHandle {
-        SkinHandle::new(self.c_ptr(), unsafe { self.parent.c_ptr_mut().data })
+        SkinHandle::new(
+            self.c_ptr(),
+            unsafe { self.parent.c_ptr_mut().data },
+            self.parent.data_handle_id(),
+        )
     }
 }
 
 impl<'a> CTmpMut<'a, Skeleton, Skin> {
     #[must_use]
     pub fn handle(&self) -> SkinHandle {
-        SkinHandle::new(self.c_ptr(), unsafe { self.parent.c_ptr_mut().data })
+        SkinHandle::new(
+            self.c_ptr(),
+            unsafe { self.parent.c_ptr_mut().data },
+            self.parent.data_handle_id(),
+        )
     }
 }
 
@@ -167,4 +277,49 @@ mod test {
         drop(skeleton.data().default_skin());
         drop(Skin::new("test"));
     }
+
+    /// Check that skins can be remapped to regions in another atlas.
+    #[cfg(not(feature = "spine38"))]
+    #[test]
+    fn skin_remap_regions() {
+        use crate::atlas::AtlasBuilder;
+
+        let (skeleton, _) = TestAsset::spineboy().instance();
+        let mut builder = AtlasBuilder::from_atlas(&TestAsset::spineboy().atlas());
+        for page in &mut builder.pages {
+            page.name = format!("remapped-{}", page.name);
+            page.width *= 2;
+        }
+        let atlas = builder.build("").unwrap();
+
+        let mut skin = skeleton.data().default_skin().clone();
+        let uvs = |skin: &Skin| {
+            skin.attachments()
+                .iter()
+                .filter_map(|entry| entry.attachment.as_region())
+                .map(|region| region.uvs())
+                .collect::<Vec<_>>()
+        };
+        let original_uvs = uvs(&skin);
+        let data_uvs = uvs(&skeleton.data().default_skin());
+        assert!(matches!(
+            unsafe { skin.remap_regions(&atlas, |_| Some("missing".to_owned())) },
+            Err(SpineError::NotFound { .. })
+        ));
+        assert_eq!(uvs(&skin), original_uvs);
+
+        let remapped = unsafe { skin.remap_regions(&atlas, |name| Some(name.to_owned())) }.unwrap();
+        assert!(remapped > 0);
+        for (remapped_uvs, original_uvs) in uvs(&skin).iter().zip(original_uvs.iter()) {
+            assert!((remapped_uvs[0] * 2. - original_uvs[0]).abs() < 0.0001);
+        }
+        for entry in skin.attachments() {
+            if let Some(region) = entry.attachment.as_region() {
+                let mut renderer_object = region.renderer_object();
+                let atlas_region = unsafe { renderer_object.get_atlas_region() }.unwrap();
+                assert!(atlas_region.page().name().starts_with("remapped-"));
+            }
+        }
+        assert_eq!(uvs(&skeleton.data().default_skin()), data_uvs);
+    }
 }
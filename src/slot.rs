@@ -7,7 +7,7 @@ use crate::{
         spSlot, spSlotData, spSlotData_setAttachmentName, spSlot_setAttachment,
         spSlot_setToSetupPose,
     },
-    c_interface::{to_c_str, CTmpRef, NewFromPtr, SyncPtr},
+    c_interface::{to_c_str, CTmpMut, CTmpRef, NewFromPtr, SyncPtr},
     AttachmentType, BoneData, BoundingBoxAttachment, ClippingAttachment, MeshAttachment,
     PointAttachment, RegionAttachment, Skeleton,
 };
@@ -37,7 +37,6 @@ macro_rules! attachment_accessor {
             let attachment = unsafe { self.c_ptr_ref().attachment };
             if !attachment.is_null() {
                 if AttachmentType::from(unsafe { (*attachment).type_0 }) == $attachment_type {
-                    #[allow(unused_unsafe)]
                     Some(CTmpRef::new(self, unsafe {
                         ($type::new_from_ptr(attachment.cast::<$c_type>()))
                     }))
@@ -77,10 +76,18 @@ impl Slot {
         }
     }
 
-    /// Create a persistent [`SlotHandle`] to this [`Slot`].
+    /// Create a persistent [`SlotHandle`] to this [`Slot`], owned by `skeleton`.
+    ///
+    /// If `skeleton` does not own this slot, the handle never resolves.
     #[must_use]
-    pub fn handle(&self) -> SlotHandle {
-        SlotHandle::new(self.c_ptr(), unsafe { self.bone().c_ptr_mut().skeleton })
+    pub fn handle(&self, skeleton: &Skeleton) -> SlotHandle {
+        let c_skeleton = unsafe { self.bone().c_ptr_mut().skeleton };
+        let skeleton_id = if c_skeleton == skeleton.c_ptr() {
+            skeleton.handle_id()
+        } else {
+            0
+        };
+        SlotHandle::new(self.c_ptr(), c_skeleton, skeleton_id)
     }
 
     attachment_accessor!(
@@ -215,6 +222,20 @@ c_handle_decl!(
     spSkeleton
 );
 
+impl<'a> CTmpRef<'a, Skeleton, Slot> {
+    #[must_use]
+    pub fn handle(&self) -> SlotHandle {
+        SlotHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
+    }
+}
+
+impl<'a> CTmpMut<'a, Skeleton, Slot> {
+    #[must_use]
+    pub fn handle(&self) -> SlotHandle {
+        SlotHandle::new(self.c_ptr(), self.parent.c_ptr(), self.parent.handle_id())
+    }
+}
+
 /// Static slot data imported from Spine.
 ///
 /// [Spine API Reference](http://esotericsoftware.com/spine-api-reference#SlotData)
@@ -284,6 +305,7 @@ impl SlotData {
 
 /// The variants of blend modes supported by Spine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendMode {
     Normal = 0,
     Additive = 1,
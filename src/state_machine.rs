@@ -0,0 +1,670 @@
+//! Provides [`AnimationStateMachine`], a data-driven layer for choosing which animations play on
+//! an [`AnimationState`].
+//!
+//! Rather than calling [`AnimationState::set_animation_by_name`] directly from gameplay code, a
+//! [`StateMachineDesc`] describes a set of states (each bound to an animation and track), the
+//! parameters gameplay code may set, and the transitions between states. Transitions fire when
+//! all of their conditions hold and, optionally, once the current animation has played for a
+//! given fraction of its duration.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use rusty_spine::{state_machine::*, *};
+//!
+//! # let atlas = Arc::new(Atlas::new_from_file("assets/spineboy/export/spineboy.atlas").unwrap());
+//! # let skeleton_json = SkeletonJson::new(atlas);
+//! # let skeleton_data = Arc::new(
+//! #     skeleton_json
+//! #         .read_skeleton_data_file("assets/spineboy/export/spineboy-pro.json")
+//! #         .unwrap(),
+//! # );
+//! # let animation_state_data = Arc::new(AnimationStateData::new(skeleton_data.clone()));
+//! let mut animation_state = AnimationState::new(animation_state_data);
+//! let desc = StateMachineDesc::new("idle")
+//!     .with_parameter("speed", ParameterValue::Float(0.))
+//!     .with_state(StateDesc::new("idle", "idle"))
+//!     .with_state(StateDesc::new("run", "run"))
+//!     .with_transition(
+//!         TransitionDesc::new("idle", "run")
+//!             .with_condition(Condition::new("speed", ConditionOp::Greater(0.5))),
+//!     )
+//!     .with_transition(
+//!         TransitionDesc::new("run", "idle")
+//!             .with_condition(Condition::new("speed", ConditionOp::Less(0.5)))
+//!             .with_mix_duration(0.4),
+//!     );
+//! let mut state_machine = AnimationStateMachine::new(&desc, &skeleton_data).unwrap();
+//! state_machine.start(&mut animation_state);
+//!
+//! state_machine.set_float("speed", 2.).unwrap();
+//! animation_state.update(0.016);
+//! state_machine.update(&mut animation_state);
+//! assert_eq!(state_machine.current_state(), "run");
+//! ```
+//!
+//! With the `serde` feature enabled, [`StateMachineDesc`] can be loaded from any serde format,
+//! such as JSON or RON.
+
+use crate::{error::SpineError, AnimationState, SkeletonData};
+
+/// The value of a state machine parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParameterValue {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    /// A boolean which is automatically reset after it causes a transition.
+    Trigger(bool),
+}
+
+/// A comparison against the value of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConditionOp {
+    /// The float or int parameter is greater than the value.
+    Greater(f32),
+    /// The float or int parameter is less than the value.
+    Less(f32),
+    /// The int parameter is equal to the value.
+    Equals(i32),
+    /// The int parameter is not equal to the value.
+    NotEquals(i32),
+    /// The bool parameter is true.
+    IsTrue,
+    /// The bool parameter is false.
+    IsFalse,
+    /// The trigger parameter has been set.
+    Triggered,
+}
+
+impl ConditionOp {
+    fn evaluate(&self, value: ParameterValue) -> Option<bool> {
+        Some(match (*self, value) {
+            (Self::Greater(threshold), ParameterValue::Float(value)) => value > threshold,
+            (Self::Greater(threshold), ParameterValue::Int(value)) => value as f32 > threshold,
+            (Self::Less(threshold), ParameterValue::Float(value)) => value < threshold,
+            (Self::Less(threshold), ParameterValue::Int(value)) => (value as f32) < threshold,
+            (Self::Equals(expected), ParameterValue::Int(value)) => value == expected,
+            (Self::NotEquals(expected), ParameterValue::Int(value)) => value != expected,
+            (Self::IsTrue, ParameterValue::Bool(value)) => value,
+            (Self::IsFalse, ParameterValue::Bool(value)) => !value,
+            (Self::Triggered, ParameterValue::Trigger(value)) => value,
+            _ => return None,
+        })
+    }
+}
+
+/// A condition which must hold for a transition to fire.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Condition {
+    pub parameter: String,
+    pub op: ConditionOp,
+}
+
+impl Condition {
+    #[must_use]
+    pub fn new(parameter: &str, op: ConditionOp) -> Self {
+        Self {
+            parameter: parameter.to_owned(),
+            op,
+        }
+    }
+}
+
+/// A named state which plays a single animation on a track.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDesc {
+    pub name: String,
+    /// The name of the animation in the [`SkeletonData`].
+    pub animation: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub track: usize,
+    #[cfg_attr(feature = "serde", serde(default = "default_looping"))]
+    pub looping: bool,
+}
+
+#[cfg(feature = "serde")]
+const fn default_looping() -> bool {
+    true
+}
+
+impl StateDesc {
+    /// Create a looping state on track 0.
+    #[must_use]
+    pub fn new(name: &str, animation: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            animation: animation.to_owned(),
+            track: 0,
+            looping: true,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_track(mut self, track: usize) -> Self {
+        self.track = track;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+}
+
+/// A transition between two states.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransitionDesc {
+    /// The state this transition leaves from, or [`None`] for a transition which may fire from
+    /// any state (except the target state itself).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub from: Option<String>,
+    pub to: String,
+    /// All conditions must hold for the transition to fire. A transition without conditions
+    /// fires as soon as its exit time (if any) is reached.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub conditions: Vec<Condition>,
+    /// If set, the transition cannot fire until the current animation has played for this
+    /// fraction of its duration (1 being one full play through).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub exit_time: Option<f32>,
+    /// If set, overrides the mix duration from the
+    /// [`AnimationStateData`](`crate::AnimationStateData`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mix_duration: Option<f32>,
+}
+
+impl TransitionDesc {
+    #[must_use]
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: Some(from.to_owned()),
+            to: to.to_owned(),
+            conditions: vec![],
+            exit_time: None,
+            mix_duration: None,
+        }
+    }
+
+    /// Create a transition which may fire from any state.
+    #[must_use]
+    pub fn new_from_any(to: &str) -> Self {
+        Self {
+            from: None,
+            to: to.to_owned(),
+            conditions: vec![],
+            exit_time: None,
+            mix_duration: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_exit_time(mut self, exit_time: f32) -> Self {
+        self.exit_time = Some(exit_time);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_mix_duration(mut self, mix_duration: f32) -> Self {
+        self.mix_duration = Some(mix_duration);
+        self
+    }
+}
+
+/// A full description of an [`AnimationStateMachine`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateMachineDesc {
+    /// The name of the state entered by [`AnimationStateMachine::start`].
+    pub initial_state: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub parameters: Vec<(String, ParameterValue)>,
+    pub states: Vec<StateDesc>,
+    /// Transitions are checked in order, with any-state transitions checked first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transitions: Vec<TransitionDesc>,
+}
+
+impl StateMachineDesc {
+    #[must_use]
+    pub fn new(initial_state: &str) -> Self {
+        Self {
+            initial_state: initial_state.to_owned(),
+            parameters: vec![],
+            states: vec![],
+            transitions: vec![],
+        }
+    }
+
+    /// Add a parameter with its default value.
+    #[must_use]
+    pub fn with_parameter(mut self, name: &str, default: ParameterValue) -> Self {
+        self.parameters.push((name.to_owned(), default));
+        self
+    }
+
+    #[must_use]
+    pub fn with_state(mut self, state: StateDesc) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    #[must_use]
+    pub fn with_transition(mut self, transition: TransitionDesc) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    name: String,
+    animation: String,
+    track: usize,
+    looping: bool,
+    duration: f32,
+}
+
+#[derive(Debug)]
+struct Transition {
+    from: Option<usize>,
+    to: usize,
+    conditions: Vec<(usize, ConditionOp)>,
+    exit_time: Option<f32>,
+    mix_duration: Option<f32>,
+}
+
+/// Drives an [`AnimationState`] using parameter-driven transitions between states, see the
+/// [module docs](`self`).
+///
+/// The state machine does not own the [`AnimationState`], so it can be used alongside a
+/// [`SkeletonController`](`crate::controller::SkeletonController`). Call
+/// [`AnimationStateMachine::update`] once per frame, after updating the animation state.
+#[derive(Debug)]
+pub struct AnimationStateMachine {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    parameters: Vec<(String, ParameterValue)>,
+    initial_state: usize,
+    current_state: usize,
+}
+
+impl AnimationStateMachine {
+    /// Validate a description against the skeleton data and create a state machine from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if an animation, state, or parameter referenced by the
+    /// description does not exist, or [`SpineError::CreationFailed`] if a condition cannot be
+    /// evaluated against the type of its parameter.
+    pub fn new(desc: &StateMachineDesc, skeleton_data: &SkeletonData) -> Result<Self, SpineError> {
+        let states = desc
+            .states
+            .iter()
+            .map(|state| {
+                let animation = skeleton_data
+                    .animations()
+                    .find(|animation| animation.name() == state.animation)
+                    .ok_or_else(|| SpineError::new_not_found("Animation", &state.animation))?;
+                Ok(State {
+                    name: state.name.clone(),
+                    animation: state.animation.clone(),
+                    track: state.track,
+                    looping: state.looping,
+                    duration: animation.duration(),
+                })
+            })
+            .collect::<Result<Vec<_>, SpineError>>()?;
+        let find_state = |name: &str| {
+            states
+                .iter()
+                .position(|state| state.name == name)
+                .ok_or_else(|| SpineError::new_not_found("State", name))
+        };
+        let transitions = desc
+            .transitions
+            .iter()
+            .map(|transition| {
+                let conditions = transition
+                    .conditions
+                    .iter()
+                    .map(|condition| {
+                        let index = desc
+                            .parameters
+                            .iter()
+                            .position(|(name, _)| *name == condition.parameter)
+                            .ok_or_else(|| {
+                                SpineError::new_not_found("Parameter", &condition.parameter)
+                            })?;
+                        if condition.op.evaluate(desc.parameters[index].1).is_none() {
+                            return Err(SpineError::new_creation_failed(&format!(
+                                "condition {:?} on parameter {}",
+                                condition.op, condition.parameter
+                            )));
+                        }
+                        Ok((index, condition.op))
+                    })
+                    .collect::<Result<Vec<_>, SpineError>>()?;
+                Ok(Transition {
+                    from: transition.from.as_deref().map(find_state).transpose()?,
+                    to: find_state(&transition.to)?,
+                    conditions,
+                    exit_time: transition.exit_time,
+                    mix_duration: transition.mix_duration,
+                })
+            })
+            .collect::<Result<Vec<_>, SpineError>>()?;
+        let initial_state = find_state(&desc.initial_state)?;
+        Ok(Self {
+            states,
+            transitions,
+            parameters: desc.parameters.clone(),
+            initial_state,
+            current_state: initial_state,
+        })
+    }
+
+    /// Enter the initial state, setting its animation on the animation state.
+    pub fn start(&mut self, animation_state: &mut AnimationState) {
+        self.enter(animation_state, self.initial_state, None);
+    }
+
+    /// Evaluate transitions from the current state, firing at most one. Returns true if a
+    /// transition fired.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn update(&mut self, animation_state: &mut AnimationState) -> bool {
+        let current = &self.states[self.current_state];
+        let progress = animation_state
+            .get_current(current.track)
+            .map_or(0., |track_entry| {
+                if current.duration > 0. {
+                    track_entry.track_time() / current.duration
+                } else {
+                    f32::INFINITY
+                }
+            });
+        let any_state = self
+            .transitions
+            .iter()
+            .filter(|transition| transition.from.is_none() && transition.to != self.current_state);
+        let from_current = self
+            .transitions
+            .iter()
+            .filter(|transition| transition.from == Some(self.current_state));
+        let Some(transition) = any_state.chain(from_current).find(|transition| {
+            transition
+                .exit_time
+                .map_or(true, |exit_time| progress >= exit_time)
+                && transition.conditions.iter().all(|(parameter, op)| {
+                    op.evaluate(self.parameters[*parameter].1).unwrap_or(false)
+                })
+        }) else {
+            return false;
+        };
+        let (to, mix_duration) = (transition.to, transition.mix_duration);
+        for (parameter, op) in &transition.conditions {
+            if *op == ConditionOp::Triggered {
+                self.parameters[*parameter].1 = ParameterValue::Trigger(false);
+            }
+        }
+        self.enter(animation_state, to, mix_duration);
+        true
+    }
+
+    /// Immediately enter a state, ignoring transitions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if the state does not exist.
+    pub fn set_state(
+        &mut self,
+        animation_state: &mut AnimationState,
+        name: &str,
+    ) -> Result<(), SpineError> {
+        let index = self
+            .states
+            .iter()
+            .position(|state| state.name == name)
+            .ok_or_else(|| SpineError::new_not_found("State", name))?;
+        self.enter(animation_state, index, None);
+        Ok(())
+    }
+
+    fn enter(
+        &mut self,
+        animation_state: &mut AnimationState,
+        index: usize,
+        mix_duration: Option<f32>,
+    ) {
+        let previous = &self.states[self.current_state];
+        let state = &self.states[index];
+        if previous.track != state.track {
+            let mix_duration = mix_duration.unwrap_or_else(|| animation_state.data().default_mix());
+            animation_state.set_empty_animation(previous.track, mix_duration);
+        }
+        // SAFETY: animation names were validated against the skeleton data in `new`.
+        let mut track_entry = unsafe {
+            animation_state.set_animation_by_name_unchecked(
+                state.track,
+                &state.animation,
+                state.looping,
+            )
+        };
+        if let Some(mix_duration) = mix_duration {
+            track_entry.set_mix_duration(mix_duration);
+        }
+        self.current_state = index;
+    }
+
+    /// The name of the current state.
+    #[must_use]
+    pub fn current_state(&self) -> &str {
+        &self.states[self.current_state].name
+    }
+
+    /// The current value of a parameter.
+    #[must_use]
+    pub fn parameter(&self, name: &str) -> Option<ParameterValue> {
+        self.parameters
+            .iter()
+            .find(|(parameter, _)| parameter == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Set a parameter. The value must be of the same type as the parameter's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no parameter exists with this name and type.
+    pub fn set_parameter(&mut self, name: &str, value: ParameterValue) -> Result<(), SpineError> {
+        let parameter = self
+            .parameters
+            .iter_mut()
+            .find(|(parameter, current)| {
+                parameter == name
+                    && std::mem::discriminant(current) == std::mem::discriminant(&value)
+            })
+            .ok_or_else(|| SpineError::new_not_found("Parameter", name))?;
+        parameter.1 = value;
+        Ok(())
+    }
+
+    /// Set a float parameter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no float parameter exists with this name.
+    pub fn set_float(&mut self, name: &str, value: f32) -> Result<(), SpineError> {
+        self.set_parameter(name, ParameterValue::Float(value))
+    }
+
+    /// Set an int parameter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no int parameter exists with this name.
+    pub fn set_int(&mut self, name: &str, value: i32) -> Result<(), SpineError> {
+        self.set_parameter(name, ParameterValue::Int(value))
+    }
+
+    /// Set a bool parameter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no bool parameter exists with this name.
+    pub fn set_bool(&mut self, name: &str, value: bool) -> Result<(), SpineError> {
+        self.set_parameter(name, ParameterValue::Bool(value))
+    }
+
+    /// Set a trigger, which stays set until it causes a transition or is reset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no trigger parameter exists with this name.
+    pub fn set_trigger(&mut self, name: &str) -> Result<(), SpineError> {
+        self.set_parameter(name, ParameterValue::Trigger(true))
+    }
+
+    /// Reset a trigger without causing a transition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::NotFound`] if no trigger parameter exists with this name.
+    pub fn reset_trigger(&mut self, name: &str) -> Result<(), SpineError> {
+        self.set_parameter(name, ParameterValue::Trigger(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test::TestAsset, AnimationState};
+
+    use super::*;
+
+    fn desc() -> StateMachineDesc {
+        StateMachineDesc::new("idle")
+            .with_parameter("speed", ParameterValue::Float(0.))
+            .with_parameter("jump", ParameterValue::Trigger(false))
+            .with_parameter("dead", ParameterValue::Bool(false))
+            .with_state(StateDesc::new("idle", "idle"))
+            .with_state(StateDesc::new("run", "run"))
+            .with_state(StateDesc::new("jump", "jump").with_looping(false))
+            .with_state(StateDesc::new("death", "death").with_looping(false))
+            .with_transition(
+                TransitionDesc::new_from_any("death")
+                    .with_condition(Condition::new("dead", ConditionOp::IsTrue)),
+            )
+            .with_transition(
+                TransitionDesc::new("idle", "run")
+                    .with_condition(Condition::new("speed", ConditionOp::Greater(0.5))),
+            )
+            .with_transition(
+                TransitionDesc::new("run", "jump")
+                    .with_condition(Condition::new("jump", ConditionOp::Triggered))
+                    .with_mix_duration(0.1),
+            )
+            .with_transition(TransitionDesc::new("jump", "run").with_exit_time(1.))
+    }
+
+    #[test]
+    fn state_machine_transitions() {
+        let (skeleton_data, animation_state_data) = TestAsset::spineboy().instance_data();
+        let mut animation_state = AnimationState::new(animation_state_data);
+        let mut state_machine = AnimationStateMachine::new(&desc(), &skeleton_data).unwrap();
+        state_machine.start(&mut animation_state);
+        assert_eq!(state_machine.current_state(), "idle");
+        assert!(!state_machine.update(&mut animation_state));
+
+        state_machine.set_float("speed", 1.).unwrap();
+        assert!(state_machine.update(&mut animation_state));
+        assert_eq!(state_machine.current_state(), "run");
+
+        state_machine.set_trigger("jump").unwrap();
+        assert!(state_machine.update(&mut animation_state));
+        assert_eq!(state_machine.current_state(), "jump");
+        assert_eq!(
+            state_machine.parameter("jump"),
+            Some(ParameterValue::Trigger(false))
+        );
+        assert_eq!(animation_state.get_current(0).unwrap().mix_duration(), 0.1);
+
+        // Jump must play out fully before returning to run.
+        animation_state.update(0.1);
+        assert!(!state_machine.update(&mut animation_state));
+        for _ in 0..100 {
+            animation_state.update(0.1);
+        }
+        assert!(state_machine.update(&mut animation_state));
+        assert_eq!(state_machine.current_state(), "run");
+
+        state_machine.set_bool("dead", true).unwrap();
+        assert!(state_machine.update(&mut animation_state));
+        assert_eq!(state_machine.current_state(), "death");
+        // Any-state transitions do not re-enter their target.
+        assert!(!state_machine.update(&mut animation_state));
+    }
+
+    #[test]
+    fn state_machine_validation() {
+        let skeleton_data = TestAsset::spineboy().skeleton_data();
+        let bad_animation = desc().with_state(StateDesc::new("fly", "fly"));
+        assert!(matches!(
+            AnimationStateMachine::new(&bad_animation, &skeleton_data),
+            Err(SpineError::NotFound { .. })
+        ));
+        let bad_condition = desc().with_transition(
+            TransitionDesc::new("idle", "run")
+                .with_condition(Condition::new("dead", ConditionOp::Greater(1.))),
+        );
+        assert!(matches!(
+            AnimationStateMachine::new(&bad_condition, &skeleton_data),
+            Err(SpineError::CreationFailed { .. })
+        ));
+        let mut state_machine = AnimationStateMachine::new(&desc(), &skeleton_data).unwrap();
+        assert!(state_machine.set_int("speed", 1).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_machine_desc_json() {
+        let json = r#"{
+            "initial_state": "idle",
+            "parameters": [["speed", { "Float": 0.0 }]],
+            "states": [
+                { "name": "idle", "animation": "idle" },
+                { "name": "run", "animation": "run", "track": 1 }
+            ],
+            "transitions": [
+                {
+                    "from": "idle",
+                    "to": "run",
+                    "conditions": [{ "parameter": "speed", "op": { "Greater": 0.5 } }],
+                    "mix_duration": 0.2
+                }
+            ]
+        }"#;
+        let desc: StateMachineDesc = serde_json::from_str(json).unwrap();
+        assert!(desc.states[0].looping);
+        assert_eq!(desc.states[1].track, 1);
+        let skeleton_data = TestAsset::spineboy().skeleton_data();
+        let mut state_machine = AnimationStateMachine::new(&desc, &skeleton_data).unwrap();
+        let (_, animation_state_data) = TestAsset::spineboy().instance_data();
+        let mut animation_state = AnimationState::new(animation_state_data);
+        state_machine.start(&mut animation_state);
+        state_machine.set_float("speed", 1.).unwrap();
+        assert!(state_machine.update(&mut animation_state));
+        assert!(animation_state.get_current(1).is_some());
+    }
+}
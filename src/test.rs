@@ -66,6 +66,10 @@ impl TestAsset {
         &Self::all()[0]
     }
 
+    pub const fn dragon() -> &'static Self {
+        &Self::all()[3]
+    }
+
     #[allow(clippy::missing_panics_doc)]
     pub fn atlas(&self) -> Atlas {
         Atlas::new(self.atlas_data, "").unwrap()
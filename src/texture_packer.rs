@@ -0,0 +1,940 @@
+//! Pack loose images into atlas pages.
+//!
+//! Requires the `texture_packer` feature.
+//!
+//! ```
+//! use rusty_spine::texture_packer::{RgbaImage, TexturePacker, TexturePackerSettings};
+//!
+//! let mut packer = TexturePacker::new(TexturePackerSettings::default().with_padding(2));
+//! packer.add_image("head", RgbaImage::from_pixel(64, 64, [255, 0, 0, 255].into()));
+//! packer.add_image("eye", RgbaImage::from_pixel(16, 16, [0, 0, 255, 255].into()));
+//! let packed = packer.pack("skeleton").unwrap();
+//! assert_eq!(packed.pages.len(), 1);
+//! assert!(packed.atlas.find_region("eye").is_some());
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+use image::imageops;
+
+use crate::{
+    atlas::{AtlasBuilder, AtlasPageDesc, AtlasRegion, AtlasRegionDesc},
+    error::SpineError,
+    Atlas, Skin,
+};
+
+pub use image::RgbaImage;
+
+/// Settings for a [`TexturePacker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TexturePackerSettings {
+    /// The maximum width of each page, in pixels.
+    pub max_width: u32,
+    /// The maximum height of each page, in pixels.
+    pub max_height: u32,
+    /// The number of pixels between regions.
+    pub padding: u32,
+    /// Strip fully transparent rows and columns from the edges of each image.
+    pub trim: bool,
+    /// Allow images to be rotated 90 degrees if they fit better.
+    pub rotation: bool,
+    /// Round the size of each page up to a power of two.
+    pub power_of_two: bool,
+    /// Multiply the color of each pixel by its alpha and mark the pages as premultiplied.
+    pub premultiplied_alpha: bool,
+}
+
+impl Default for TexturePackerSettings {
+    fn default() -> Self {
+        Self {
+            max_width: 2048,
+            max_height: 2048,
+            padding: 2,
+            trim: true,
+            rotation: false,
+            power_of_two: true,
+            premultiplied_alpha: false,
+        }
+    }
+}
+
+impl TexturePackerSettings {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_max_size(self, max_width: u32, max_height: u32) -> Self {
+        Self {
+            max_width,
+            max_height,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn with_padding(self, padding: u32) -> Self {
+        Self { padding, ..self }
+    }
+
+    #[must_use]
+    pub const fn with_trim(self, trim: bool) -> Self {
+        Self { trim, ..self }
+    }
+
+    #[must_use]
+    pub const fn with_rotation(self, rotation: bool) -> Self {
+        Self { rotation, ..self }
+    }
+
+    #[must_use]
+    pub const fn with_power_of_two(self, power_of_two: bool) -> Self {
+        Self {
+            power_of_two,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn with_premultiplied_alpha(self, premultiplied_alpha: bool) -> Self {
+        Self {
+            premultiplied_alpha,
+            ..self
+        }
+    }
+}
+
+/// Packs RGBA images into one or more pages and describes them with an [`Atlas`].
+#[derive(Debug, Clone, Default)]
+pub struct TexturePacker {
+    settings: TexturePackerSettings,
+    images: Vec<PackerImage>,
+}
+
+#[derive(Debug, Clone)]
+struct PackerImage {
+    name: String,
+    index: i32,
+    image: RgbaImage,
+}
+
+/// An image placed on a page.
+struct Placement {
+    image: usize,
+    page: usize,
+    rect: Rect,
+    rotated: bool,
+    /// The trimmed bounds within the source image.
+    trim: Rect,
+}
+
+impl TexturePacker {
+    #[must_use]
+    pub const fn new(settings: TexturePackerSettings) -> Self {
+        Self {
+            settings,
+            images: vec![],
+        }
+    }
+
+    #[must_use]
+    pub const fn settings(&self) -> &TexturePackerSettings {
+        &self.settings
+    }
+
+    /// Add an image to be packed as a region named `name`.
+    pub fn add_image(&mut self, name: &str, image: RgbaImage) {
+        self.add_sequence_image(name, -1, image);
+    }
+
+    /// Add an image to be packed as frame `index` of the sequence `name`.
+    pub fn add_sequence_image(&mut self, name: &str, index: i32, image: RgbaImage) {
+        self.images.push(PackerImage {
+            name: name.to_owned(),
+            index,
+            image,
+        });
+    }
+
+    /// Pack all added images into pages named `name.png`, `name_2.png`, etc.
+    ///
+    /// Images are packed largest first using the max rects algorithm, starting a new page when an
+    /// image does not fit on any existing page.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::ImageTooLarge`] if an image does not fit on an empty page. Returns any
+    /// error from [`AtlasBuilder::build`].
+    pub fn pack(&self, name: &str) -> Result<PackedAtlas, SpineError> {
+        let settings = &self.settings;
+        let padding = settings.padding;
+        let trims: Vec<Rect> = self
+            .images
+            .iter()
+            .map(|image| {
+                if settings.trim {
+                    trim_bounds(&image.image)
+                } else {
+                    Rect::new(0, 0, image.image.width(), image.image.height())
+                }
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by(|a, b| {
+            let (a_trim, b_trim) = (trims[*a], trims[*b]);
+            b_trim
+                .w
+                .max(b_trim.h)
+                .cmp(&a_trim.w.max(a_trim.h))
+                .then((b_trim.w * b_trim.h).cmp(&(a_trim.w * a_trim.h)))
+                .then(self.images[*a].name.cmp(&self.images[*b].name))
+                .then(self.images[*a].index.cmp(&self.images[*b].index))
+        });
+
+        // Padding is only added to the right and bottom of each region, so the bins are enlarged
+        // by the padding to avoid requiring it at the edges of the page.
+        let mut bins: Vec<MaxRectsBin> = vec![];
+        let mut placements = vec![];
+        for image_index in order {
+            let trim = trims[image_index];
+            let (width, height) = (trim.w + padding, trim.h + padding);
+            let mut placed = None;
+            for (page, bin) in bins.iter_mut().enumerate() {
+                if let Some((rect, rotated)) = bin.insert(width, height, settings.rotation) {
+                    placed = Some((page, rect, rotated));
+                    break;
+                }
+            }
+            if placed.is_none() {
+                let mut bin =
+                    MaxRectsBin::new(settings.max_width + padding, settings.max_height + padding);
+                let Some((rect, rotated)) = bin.insert(width, height, settings.rotation) else {
+                    let image = &self.images[image_index];
+                    return Err(SpineError::ImageTooLarge {
+                        name: image.name.clone(),
+                        width: image.image.width(),
+                        height: image.image.height(),
+                    });
+                };
+                bins.push(bin);
+                placed = Some((bins.len() - 1, rect, rotated));
+            }
+            if let Some((page, rect, rotated)) = placed {
+                placements.push(Placement {
+                    image: image_index,
+                    page,
+                    rect: Rect::new(rect.x, rect.y, rect.w - padding, rect.h - padding),
+                    rotated,
+                    trim,
+                });
+            }
+        }
+
+        let mut builder = AtlasBuilder::new();
+        let mut pages = vec![];
+        for page_index in 0..bins.len() {
+            let page_placements = placements
+                .iter()
+                .filter(|placement| placement.page == page_index);
+            let (mut width, mut height) =
+                page_placements
+                    .clone()
+                    .fold((1, 1), |(width, height), placement| {
+                        (
+                            width.max(placement.rect.x + placement.rect.w),
+                            height.max(placement.rect.y + placement.rect.h),
+                        )
+                    });
+            if settings.power_of_two {
+                width = width.next_power_of_two();
+                height = height.next_power_of_two();
+            }
+            let page_name = if page_index == 0 {
+                format!("{name}.png")
+            } else {
+                format!("{name}_{}.png", page_index + 1)
+            };
+            let mut image = RgbaImage::new(width, height);
+            let mut page_desc = AtlasPageDesc::new(&page_name, width as i32, height as i32)
+                .with_pma(settings.premultiplied_alpha);
+            for placement in page_placements {
+                let source = &self.images[placement.image];
+                let trim = placement.trim;
+                let trimmed =
+                    imageops::crop_imm(&source.image, trim.x, trim.y, trim.w, trim.h).to_image();
+                // Spine expects rotated regions to be stored rotated 90 degrees counter clockwise.
+                let stored = if placement.rotated {
+                    imageops::rotate270(&trimmed)
+                } else {
+                    trimmed
+                };
+                imageops::replace(
+                    &mut image,
+                    &stored,
+                    i64::from(placement.rect.x),
+                    i64::from(placement.rect.y),
+                );
+                // Spine measures offsets from the bottom left of the original image.
+                let offset_y = source.image.height() - trim.y - trim.h;
+                page_desc = page_desc.with_region(
+                    AtlasRegionDesc::new(
+                        &source.name,
+                        placement.rect.x as i32,
+                        placement.rect.y as i32,
+                        trim.w as i32,
+                        trim.h as i32,
+                    )
+                    .with_offsets(
+                        trim.x as i32,
+                        offset_y as i32,
+                        source.image.width() as i32,
+                        source.image.height() as i32,
+                    )
+                    .with_degrees(if placement.rotated { 90 } else { 0 })
+                    .with_index(source.index),
+                );
+            }
+            if settings.premultiplied_alpha {
+                premultiply_alpha(&mut image);
+            }
+            builder = builder.with_page(page_desc);
+            pages.push(PackedPage {
+                name: page_name,
+                image,
+            });
+        }
+        let atlas = builder.build("")?;
+        Ok(PackedAtlas {
+            pages,
+            atlas,
+            builder,
+        })
+    }
+}
+
+/// The result of [`TexturePacker::pack`].
+#[derive(Debug)]
+pub struct PackedAtlas {
+    /// The page images, in the same order as the pages of the atlas.
+    pub pages: Vec<PackedPage>,
+    /// The atlas describing the packed regions, ready to be used with
+    /// [`SkeletonJson`](`crate::SkeletonJson`) or [`SkeletonBinary`](`crate::SkeletonBinary`).
+    ///
+    /// The atlas is loaded without a directory, so texture callbacks receive the bare page names,
+    /// see [`PackedPage::name`].
+    pub atlas: Atlas,
+    /// The description of the atlas, which can be used to write an `.atlas` file.
+    pub builder: AtlasBuilder,
+}
+
+impl PackedAtlas {
+    /// Write the `.atlas` file to `atlas_path` and each page image as a PNG next to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpineError::FailedToWriteFile`] if any file could not be written.
+    pub fn save<P: AsRef<Path>>(&self, atlas_path: P) -> Result<(), SpineError> {
+        let atlas_path = atlas_path.as_ref();
+        let dir = atlas_path.parent().unwrap_or_else(|| Path::new(""));
+        for page in &self.pages {
+            let page_path = dir.join(&page.name);
+            page.image
+                .save(&page_path)
+                .map_err(|_| SpineError::new_failed_to_write_file(&page_path))?;
+        }
+        std::fs::write(atlas_path, self.builder.to_string())
+            .map_err(|_| SpineError::new_failed_to_write_file(atlas_path))
+    }
+}
+
+/// A page image produced by [`TexturePacker::pack`].
+#[derive(Debug, Clone)]
+pub struct PackedPage {
+    /// The file name of the page, as written in the atlas.
+    pub name: String,
+    pub image: RgbaImage,
+}
+
+/// Pack the atlas regions used by `skin` into a single page, returning a copy of the skin whose
+/// attachments point at the packed regions, like `Skin.getRepackedSkin` in spine-unity.
+///
+/// Skins combining several other skins (see [`Skin::add_skin`]) often draw from many atlas pages,
+/// which prevents [`CombinedDrawer`](`crate::draw::CombinedDrawer`) from batching them. The
+/// repacked skin draws from one page, named `name.png`.
+///
+/// `page_images` holds the image of each atlas page used by the skin, keyed by
+/// [`AtlasPage::name`](`crate::atlas::AtlasPage::name`). Regions are unpacked from these images,
+/// so they should not be premultiplied unless the packed page is premultiplied as well. Regions are
+/// identified by name and index, and attachments using a sequence are left unchanged.
+///
+/// ```
+/// # #[path="./test.rs"]
+/// # mod test;
+/// # use std::collections::HashMap;
+/// use rusty_spine::texture_packer::{repack_skin, TexturePackerSettings};
+///
+/// # use rusty_spine::Skeleton;
+/// # let (skeleton_data, _) = test::TestAsset::spineboy().instance_data();
+/// # let mut skeleton = Skeleton::new(skeleton_data.clone());
+/// let page = image::open("assets/spineboy/export/spineboy.png")?.to_rgba8();
+/// let page_images = HashMap::from([("spineboy.png".to_owned(), page)]);
+/// let skin = skeleton_data.default_skin();
+/// let repacked =
+///     unsafe { repack_skin(&skin, "spineboy-repacked", &page_images, TexturePackerSettings::default()) }?;
+/// repacked.page.image.save("spineboy-repacked.png")?;
+/// # std::fs::remove_file("spineboy-repacked.png")?;
+/// unsafe { skeleton.set_skin(&repacked.skin) };
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`SpineError::NotFound`] if an image in `page_images` is missing, or
+/// [`SpineError::CreationFailed`] if a region does not fit in its page image or the regions do
+/// not fit on a single page. Returns any error
+/// from [`TexturePacker::pack`].
+///
+/// # Safety
+///
+/// The attachments in `skin` must have been loaded by the atlas attachment loader. The returned
+/// [`RepackedSkin::atlas`] must outlive every skeleton using the returned skin.
+pub unsafe fn repack_skin(
+    skin: &Skin,
+    name: &str,
+    page_images: &HashMap<String, RgbaImage>,
+    settings: TexturePackerSettings,
+) -> Result<RepackedSkin, SpineError> {
+    let mut packer = TexturePacker::new(settings);
+    let mut packed_regions = HashSet::new();
+    for (_, region) in skin.atlas_region_attachments() {
+        let index = region.c_ptr_ref().index;
+        if !packed_regions.insert((region.name().to_owned(), index)) {
+            continue;
+        }
+        let page = region.page();
+        let Some(page_image) = page_images.get(page.name()) else {
+            return Err(SpineError::new_not_found("Page image", page.name()));
+        };
+        packer.add_sequence_image(region.name(), index, unpack_region(page_image, &region)?);
+    }
+    let packed = packer.pack(name)?;
+    let Ok::<[PackedPage; 1], _>([page]) = packed.pages.try_into() else {
+        return Err(SpineError::new_creation_failed("single page atlas"));
+    };
+    let atlas = Arc::new(packed.atlas);
+    let mut repacked_skin = Skin::new(skin.name());
+    repacked_skin.copy_skin(skin);
+    remap_packed_regions(&mut repacked_skin, &atlas)?;
+    Ok(RepackedSkin {
+        skin: repacked_skin,
+        atlas,
+        page,
+        builder: packed.builder,
+    })
+}
+
+/// The result of [`repack_skin`].
+#[derive(Debug)]
+pub struct RepackedSkin {
+    /// A copy of the skin, with its attachments pointing at regions in [`RepackedSkin::atlas`].
+    pub skin: Skin,
+    /// The atlas of the packed page, which must outlive every skeleton using
+    /// [`RepackedSkin::skin`].
+    pub atlas: Arc<Atlas>,
+    /// The packed page image, which is the only page of [`RepackedSkin::atlas`].
+    pub page: PackedPage,
+    /// The description of the atlas, which can be used to write an `.atlas` file.
+    pub builder: AtlasBuilder,
+}
+
+/// Point the attachments in `skin` at the region in `atlas` with the same name and index as their
+/// current region, so indexed regions sharing a name stay distinct.
+unsafe fn remap_packed_regions(skin: &mut Skin, atlas: &Atlas) -> Result<usize, SpineError> {
+    let regions: HashMap<_, _> = atlas
+        .regions()
+        .map(|region| {
+            let index = region.c_ptr_ref().index;
+            ((region.name().to_owned(), index), region.c_ptr())
+        })
+        .collect();
+    skin.remap_regions_with(|current| {
+        let key = (current.name().to_owned(), current.c_ptr_ref().index);
+        regions
+            .get(&key)
+            .map(|c_region| Some(*c_region))
+            .ok_or_else(|| SpineError::new_not_found("AtlasRegion", current.name()))
+    })
+}
+
+/// Cut the original (untrimmed, unrotated) image of a region out of its page.
+///
+/// Regions are stored rotated counter clockwise by their degrees, which must be 0, 90, 180, or
+/// 270. Fails if the region does not fit on the page or in its original size.
+fn unpack_region(page: &RgbaImage, region: &AtlasRegion) -> Result<RgbaImage, SpineError> {
+    let c_region = unsafe { region.c_ptr_ref() };
+    #[cfg(not(feature = "spine38"))]
+    let texture_region = &c_region.super_0;
+    #[cfg(feature = "spine38")]
+    let texture_region = c_region;
+    let invalid = || SpineError::new_creation_failed(&format!("image of region {}", region.name()));
+    let size = |value: i32| u32::try_from(value).map_err(|_| invalid());
+    let offset = |value: f64| {
+        (0. ..=f64::from(u32::MAX))
+            .contains(&value)
+            .then_some(value as u32)
+            .ok_or_else(invalid)
+    };
+    let (x, y) = (size(c_region.x)?, size(c_region.y)?);
+    let (width, height) = (size(texture_region.width)?, size(texture_region.height)?);
+    let (stored_width, stored_height) = match texture_region.degrees {
+        0 | 180 => (width, height),
+        90 | 270 => (height, width),
+        _ => return Err(invalid()),
+    };
+    let fits = |start: u32, length: u32, limit: u32| {
+        start.checked_add(length).is_some_and(|end| end <= limit)
+    };
+    if !fits(x, stored_width, page.width()) || !fits(y, stored_height, page.height()) {
+        return Err(invalid());
+    }
+    let stored = imageops::crop_imm(page, x, y, stored_width, stored_height).to_image();
+    let trimmed = match texture_region.degrees {
+        90 => imageops::rotate90(&stored),
+        180 => imageops::rotate180(&stored),
+        270 => imageops::rotate270(&stored),
+        _ => stored,
+    };
+    let mut image = RgbaImage::new(
+        size(texture_region.originalWidth)?,
+        size(texture_region.originalHeight)?,
+    );
+    // Spine measures offsets from the bottom left of the original image.
+    let offset_x = offset(f64::from(texture_region.offsetX))?;
+    let offset_y = image
+        .height()
+        .checked_sub(height)
+        .and_then(|top| top.checked_sub(offset(f64::from(texture_region.offsetY)).ok()?))
+        .ok_or_else(invalid)?;
+    if !fits(offset_x, width, image.width()) {
+        return Err(invalid());
+    }
+    imageops::replace(
+        &mut image,
+        &trimmed,
+        i64::from(offset_x),
+        i64::from(offset_y),
+    );
+    Ok(image)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+impl Rect {
+    const fn new(x: u32, y: u32, w: u32, h: u32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    const fn right(&self) -> u32 {
+        self.x + self.w
+    }
+
+    const fn bottom(&self) -> u32 {
+        self.y + self.h
+    }
+
+    const fn intersects(&self, other: &Self) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    const fn contains(&self, other: &Self) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+}
+
+/// A page being packed with the max rects algorithm, placing each rect using the best short side
+/// fit heuristic.
+struct MaxRectsBin {
+    free: Vec<Rect>,
+}
+
+impl MaxRectsBin {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            free: vec![Rect::new(0, 0, width, height)],
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    fn insert(&mut self, width: u32, height: u32, rotation: bool) -> Option<(Rect, bool)> {
+        let mut best: Option<((u32, u32), Rect, bool)> = None;
+        for free in &self.free {
+            let orientations: &[(u32, u32, bool)] = if rotation && width != height {
+                &[(width, height, false), (height, width, true)]
+            } else {
+                &[(width, height, false)]
+            };
+            for &(w, h, rotated) in orientations {
+                if w > free.w || h > free.h {
+                    continue;
+                }
+                let leftover_w = free.w - w;
+                let leftover_h = free.h - h;
+                let score = (leftover_w.min(leftover_h), leftover_w.max(leftover_h));
+                if best.map_or(true, |(best_score, ..)| score < best_score) {
+                    best = Some((score, Rect::new(free.x, free.y, w, h), rotated));
+                }
+            }
+        }
+        let (_, rect, rotated) = best?;
+        self.place(rect);
+        Some((rect, rotated))
+    }
+
+    fn place(&mut self, used: Rect) {
+        let mut split = vec![];
+        self.free.retain(|free| {
+            if !free.intersects(&used) {
+                return true;
+            }
+            if used.x > free.x {
+                split.push(Rect::new(free.x, free.y, used.x - free.x, free.h));
+            }
+            if used.right() < free.right() {
+                split.push(Rect::new(
+                    used.right(),
+                    free.y,
+                    free.right() - used.right(),
+                    free.h,
+                ));
+            }
+            if used.y > free.y {
+                split.push(Rect::new(free.x, free.y, free.w, used.y - free.y));
+            }
+            if used.bottom() < free.bottom() {
+                split.push(Rect::new(
+                    free.x,
+                    used.bottom(),
+                    free.w,
+                    free.bottom() - used.bottom(),
+                ));
+            }
+            false
+        });
+        self.free.extend(split);
+        // Remove free rects which are contained by another.
+        let mut index = 0;
+        while index < self.free.len() {
+            let rect = self.free[index];
+            let contained = self
+                .free
+                .iter()
+                .enumerate()
+                .any(|(other_index, other)| other_index != index && other.contains(&rect));
+            if contained {
+                self.free.swap_remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Find the bounds of the non-transparent pixels in an image. Fully transparent images are
+/// trimmed to a single pixel.
+fn trim_bounds(image: &RgbaImage) -> Rect {
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] != 0 {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+    }
+    if left == u32::MAX {
+        Rect::new(0, 0, 1.min(image.width()), 1.min(image.height()))
+    } else {
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+fn premultiply_alpha(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = u16::from(pixel[3]);
+        for channel in 0..3 {
+            pixel[channel] = ((u16::from(pixel[channel]) * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "spine38"))]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{test::TestAsset, Skeleton, SkeletonJson};
+
+    use super::*;
+
+    fn extract_region(page: &RgbaImage, atlas: &Atlas, name: &str) -> RgbaImage {
+        unpack_region(page, &atlas.find_region(name).unwrap()).unwrap()
+    }
+
+    /// Clear the color of fully transparent pixels, which trimming does not preserve.
+    fn visible_pixels(mut image: RgbaImage) -> RgbaImage {
+        for pixel in image.pixels_mut() {
+            if pixel[3] == 0 {
+                *pixel = [0; 4].into();
+            }
+        }
+        image
+    }
+
+    /// Unpack spineboy's atlas into loose images, repack them, and check that the skeleton loads
+    /// with identical images.
+    #[test]
+    fn repack_spineboy() {
+        let asset = TestAsset::spineboy();
+        let atlas = Atlas::new_from_file(asset.atlas_file).unwrap();
+        let page = image::open(
+            Path::new(asset.atlas_file)
+                .parent()
+                .unwrap()
+                .join(atlas.pages().next().unwrap().name()),
+        )
+        .unwrap()
+        .to_rgba8();
+        let mut packer = TexturePacker::new(
+            TexturePackerSettings::default()
+                .with_max_size(256, 256)
+                .with_rotation(true),
+        );
+        for region in atlas.regions() {
+            packer.add_image(region.name(), extract_region(&page, &atlas, region.name()));
+        }
+        let packed = packer.pack("spineboy").unwrap();
+        assert!(packed.pages.len() > 1);
+        assert_eq!(packed.atlas.pages().count(), packed.pages.len());
+        assert_eq!(packed.atlas.regions().count(), atlas.regions().count());
+        for region in atlas.regions() {
+            let packed_region = packed.atlas.find_region(region.name()).unwrap();
+            let packed_page = packed
+                .pages
+                .iter()
+                .find(|page| page.name == packed_region.page().name())
+                .unwrap();
+            assert!(packed_page.image.width().is_power_of_two());
+            assert_eq!(
+                extract_region(&packed_page.image, &packed.atlas, region.name()),
+                extract_region(&page, &atlas, region.name()),
+                "{}",
+                region.name()
+            );
+        }
+        let skeleton_json = SkeletonJson::new(Arc::new(packed.atlas));
+        skeleton_json.read_skeleton_data(asset.json_data).unwrap();
+    }
+
+    /// Repack dragon's skin from several pages into one, and check that each attachment draws the
+    /// same image from the new page.
+    #[test]
+    fn repack_dragon_skin() {
+        let asset = TestAsset::dragon();
+        let (skeleton_data, _) = asset.instance_data();
+        let mut skeleton = Skeleton::new(skeleton_data.clone());
+        let dir = Path::new(asset.atlas_file).parent().unwrap();
+        let page_images: HashMap<String, RgbaImage> = asset
+            .atlas()
+            .pages()
+            .map(|page| {
+                let image = image::open(dir.join(page.name())).unwrap().to_rgba8();
+                (page.name().to_owned(), image)
+            })
+            .collect();
+        assert!(page_images.len() > 1);
+        let skin = skeleton_data.default_skin();
+        let settings = TexturePackerSettings::default();
+        let repacked = unsafe { repack_skin(&skin, "dragon", &page_images, settings) }.unwrap();
+        assert_eq!(repacked.page.name, "dragon.png");
+        assert_eq!(repacked.skin.name(), skin.name());
+
+        let original_regions = unsafe { skin.atlas_region_attachments() };
+        let repacked_regions = unsafe { repacked.skin.atlas_region_attachments() };
+        assert_eq!(repacked_regions.len(), original_regions.len());
+        for (_, region) in &repacked_regions {
+            assert_eq!(region.page().name(), "dragon.png");
+            let original = original_regions
+                .iter()
+                .find(|(_, original)| original.name() == region.name())
+                .map(|(_, original)| original)
+                .unwrap();
+            assert_eq!(
+                visible_pixels(unpack_region(&repacked.page.image, region).unwrap()),
+                visible_pixels(
+                    unpack_region(&page_images[original.page().name()], original).unwrap()
+                ),
+                "{}",
+                region.name()
+            );
+        }
+
+        unsafe { skeleton.set_skin(&repacked.skin) };
+        skeleton.update_world_transform();
+
+        let missing_page = HashMap::new();
+        assert!(matches!(
+            unsafe { repack_skin(&skin, "dragon", &missing_page, settings) },
+            Err(SpineError::NotFound { .. })
+        ));
+        let small = settings.with_max_size(512, 512);
+        assert!(matches!(
+            unsafe { repack_skin(&skin, "dragon", &page_images, small) },
+            Err(SpineError::CreationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn pack_settings() {
+        let mut image = RgbaImage::new(10, 20);
+        image.put_pixel(2, 3, [100, 50, 200, 128].into());
+        image.put_pixel(5, 15, [100, 50, 200, 255].into());
+        let mut packer = TexturePacker::new(
+            TexturePackerSettings::default()
+                .with_power_of_two(false)
+                .with_premultiplied_alpha(true),
+        );
+        packer.add_image("a", image.clone());
+        packer.add_sequence_image("b", 1, image);
+        let packed = packer.pack("test").unwrap();
+        assert!(packed.atlas.pages().next().unwrap().pma());
+        let region = packed.atlas.find_region("a").unwrap();
+        let texture_region = region.texture_region();
+        assert_eq!(texture_region.width(), 4);
+        assert_eq!(texture_region.height(), 13);
+        assert_eq!(texture_region.offset_x(), 2.);
+        assert_eq!(texture_region.offset_y(), 4.);
+        assert_eq!(texture_region.original_width(), 10);
+        let page = &packed.pages[0].image;
+        assert_eq!((page.width(), page.height()), (4, 13 + 2 + 13));
+        assert_eq!(
+            page.get_pixel(region.x() as u32, region.y() as u32).0,
+            [50, 25, 100, 128]
+        );
+        assert_eq!(
+            AtlasBuilder::from_atlas(&packed.atlas).pages[0].regions[1].index,
+            1
+        );
+
+        let mut packer = TexturePacker::new(TexturePackerSettings::default().with_max_size(16, 16));
+        packer.add_image("big", RgbaImage::from_pixel(8, 32, [255; 4].into()));
+        assert!(matches!(
+            packer.pack("test"),
+            Err(SpineError::ImageTooLarge { .. })
+        ));
+    }
+
+    /// Unpack a region stored at each rotation, and check that regions outside their page fail.
+    #[test]
+    fn unpack_rotated_regions() {
+        use crate::atlas::{AtlasPageDesc, AtlasRegionDesc};
+
+        let original = RgbaImage::from_fn(5, 4, |x, y| [x as u8, y as u8, 0, 255].into());
+        // Trim one column on the left and one row at the bottom.
+        let trimmed = imageops::crop_imm(&original, 1, 0, 4, 3).to_image();
+        let unpack = |degrees: i32, stored: &RgbaImage| {
+            let page =
+                AtlasPageDesc::new("page.png", stored.width() as i32, stored.height() as i32)
+                    .with_region(
+                        AtlasRegionDesc::new("region", 0, 0, 4, 3)
+                            .with_offsets(1, 1, 5, 4)
+                            .with_degrees(degrees),
+                    );
+            let atlas = AtlasBuilder::new().with_page(page).build("").unwrap();
+            unpack_region(stored, &atlas.find_region("region").unwrap())
+        };
+        // Clear the trimmed pixels, which are not stored.
+        let mut expected = original.clone();
+        for (x, y, pixel) in expected.enumerate_pixels_mut() {
+            if x == 0 || y == 3 {
+                *pixel = [0; 4].into();
+            }
+        }
+        for (degrees, stored) in [
+            (0, trimmed.clone()),
+            (90, imageops::rotate270(&trimmed)),
+            (180, imageops::rotate180(&trimmed)),
+            (270, imageops::rotate90(&trimmed)),
+        ] {
+            assert_eq!(unpack(degrees, &stored).unwrap(), expected, "{degrees}");
+        }
+
+        assert!(matches!(
+            unpack(0, &RgbaImage::new(3, 3)),
+            Err(SpineError::CreationFailed { .. })
+        ));
+        assert!(matches!(
+            unpack(90, &trimmed),
+            Err(SpineError::CreationFailed { .. })
+        ));
+    }
+
+    /// Check that regions sharing a name are told apart by their index when remapping a repacked
+    /// skin.
+    #[test]
+    fn remap_packed_indexed_regions() {
+        use crate::atlas::AtlasBuilder;
+
+        let asset = TestAsset::spineboy();
+        let mut builder = AtlasBuilder::from_atlas(&asset.atlas());
+        for page in &mut builder.pages {
+            for region in &mut page.regions {
+                region.index = 1;
+            }
+        }
+        let atlas = Arc::new(builder.build("").unwrap());
+        let skeleton_data = SkeletonJson::new(atlas)
+            .read_skeleton_data(asset.json_data)
+            .unwrap();
+
+        // Put a region with index 0 before each region, which a lookup by name alone would find.
+        for page in &mut builder.pages {
+            page.regions = page
+                .regions
+                .iter()
+                .flat_map(|region| {
+                    let mut first = region.clone();
+                    first.index = 0;
+                    [first, region.clone()]
+                })
+                .collect();
+        }
+        let indexed = builder.build("").unwrap();
+        let mut skin = skeleton_data.default_skin().clone();
+        let remapped = unsafe { remap_packed_regions(&mut skin, &indexed) }.unwrap();
+        assert!(remapped > 0);
+        for (_, region) in unsafe { skin.atlas_region_attachments() } {
+            assert_eq!(unsafe { region.c_ptr_ref().index }, 1);
+            assert!(indexed
+                .regions()
+                .any(|indexed_region| indexed_region.c_ptr() == region.c_ptr()));
+        }
+    }
+}
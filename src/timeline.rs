@@ -0,0 +1,207 @@
+use crate::{
+    c::{
+        spAlphaTimeline, spAttachmentTimeline, spDeformTimeline, spIkConstraintTimeline,
+        spPathConstraintMixTimeline, spRGBA2Timeline, spRGBATimeline, spRGBTimeline,
+        spRotateTimeline, spSequenceTimeline, spTimeline, spTimelineType, spTimeline_getDuration,
+        spTransformConstraintTimeline,
+    },
+    c_interface::{NewFromPtr, SyncPtr},
+};
+
+/// The base class for all timelines, which key a single property (or group of properties) of a
+/// bone, slot, or constraint over time.
+///
+/// [Spine API Reference](http://esotericsoftware.com/spine-api-reference#Timeline)
+#[derive(Debug)]
+pub struct Timeline {
+    c_timeline: SyncPtr<spTimeline>,
+}
+
+impl NewFromPtr<spTimeline> for Timeline {
+    unsafe fn new_from_ptr(c_timeline: *mut spTimeline) -> Self {
+        Self {
+            c_timeline: SyncPtr(c_timeline),
+        }
+    }
+}
+
+impl Timeline {
+    /// The type of this timeline, which determines which of
+    /// [`bone_index`](`Self::bone_index`), [`slot_index`](`Self::slot_index`), or
+    /// [`constraint_index`](`Self::constraint_index`) applies.
+    #[must_use]
+    pub fn timeline_type(&self) -> TimelineType {
+        TimelineType::from(unsafe { self.c_ptr_ref().type_0 })
+    }
+
+    /// The unique identifiers of the properties keyed by this timeline.
+    #[must_use]
+    pub fn property_ids(&self) -> &[u64] {
+        let timeline = unsafe { self.c_ptr_ref() };
+        &timeline.propertyIds[..timeline.propertyIdsCount as usize]
+    }
+
+    /// The time in seconds of the last frame.
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        unsafe { spTimeline_getDuration(self.c_ptr()) }
+    }
+
+    /// The index of the bone in [`SkeletonData::bones`](`crate::SkeletonData::bones`) keyed by
+    /// this timeline, or [`None`] if it is not a bone timeline.
+    #[must_use]
+    pub fn bone_index(&self) -> Option<usize> {
+        if self.timeline_type().is_bone_timeline() {
+            // All bone timelines share the same layout.
+            Some(unsafe { (*self.c_ptr().cast::<spRotateTimeline>()).boneIndex as usize })
+        } else {
+            None
+        }
+    }
+
+    /// The index of the slot in [`SkeletonData::slots`](`crate::SkeletonData::slots`) keyed by
+    /// this timeline, or [`None`] if it is not a slot timeline.
+    #[must_use]
+    pub fn slot_index(&self) -> Option<usize> {
+        let ptr = self.c_ptr();
+        let slot_index = unsafe {
+            match self.timeline_type() {
+                TimelineType::Attachment => (*ptr.cast::<spAttachmentTimeline>()).slotIndex,
+                TimelineType::Alpha => (*ptr.cast::<spAlphaTimeline>()).slotIndex,
+                TimelineType::Rgba => (*ptr.cast::<spRGBATimeline>()).slotIndex,
+                TimelineType::Rgb => (*ptr.cast::<spRGBTimeline>()).slotIndex,
+                TimelineType::Rgba2 | TimelineType::Rgb2 => {
+                    (*ptr.cast::<spRGBA2Timeline>()).slotIndex
+                }
+                TimelineType::Deform => (*ptr.cast::<spDeformTimeline>()).slotIndex,
+                TimelineType::Sequence => (*ptr.cast::<spSequenceTimeline>()).slotIndex,
+                _ => return None,
+            }
+        };
+        Some(slot_index as usize)
+    }
+
+    /// The index of the IK, transform, or path constraint in [`SkeletonData`](`crate::SkeletonData`)
+    /// keyed by this timeline, or [`None`] if it is not a constraint timeline.
+    #[must_use]
+    pub fn constraint_index(&self) -> Option<usize> {
+        let ptr = self.c_ptr();
+        let constraint_index = unsafe {
+            match self.timeline_type() {
+                TimelineType::IkConstraint => {
+                    (*ptr.cast::<spIkConstraintTimeline>()).ikConstraintIndex
+                }
+                TimelineType::TransformConstraint => {
+                    (*ptr.cast::<spTransformConstraintTimeline>()).transformConstraintIndex
+                }
+                TimelineType::PathConstraintPosition
+                | TimelineType::PathConstraintSpacing
+                | TimelineType::PathConstraintMix => {
+                    // All path constraint timelines share the same layout.
+                    (*ptr.cast::<spPathConstraintMixTimeline>()).pathConstraintIndex
+                }
+                _ => return None,
+            }
+        };
+        Some(constraint_index as usize)
+    }
+
+    c_accessor!(
+        /// The number of frames in this timeline.
+        frame_count,
+        frameCount,
+        usize
+    );
+    c_accessor!(
+        /// The number of entries stored per frame.
+        frame_entries,
+        frameEntries,
+        usize
+    );
+    c_ptr!(c_timeline, spTimeline);
+}
+
+/// The types of timelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimelineType {
+    Attachment = 0,
+    Alpha = 1,
+    PathConstraintPosition = 2,
+    PathConstraintSpacing = 3,
+    Rotate = 4,
+    ScaleX = 5,
+    ScaleY = 6,
+    ShearX = 7,
+    ShearY = 8,
+    TranslateX = 9,
+    TranslateY = 10,
+    Scale = 11,
+    Shear = 12,
+    Translate = 13,
+    Deform = 14,
+    Sequence = 15,
+    IkConstraint = 16,
+    PathConstraintMix = 17,
+    Rgb2 = 18,
+    Rgba2 = 19,
+    Rgba = 20,
+    Rgb = 21,
+    TransformConstraint = 22,
+    DrawOrder = 23,
+    Event = 24,
+    Unknown = 99,
+}
+
+impl TimelineType {
+    /// Returns true if timelines of this type key a property of a bone.
+    #[must_use]
+    pub const fn is_bone_timeline(self) -> bool {
+        matches!(
+            self,
+            Self::Rotate
+                | Self::ScaleX
+                | Self::ScaleY
+                | Self::ShearX
+                | Self::ShearY
+                | Self::TranslateX
+                | Self::TranslateY
+                | Self::Scale
+                | Self::Shear
+                | Self::Translate
+        )
+    }
+}
+
+impl From<spTimelineType> for TimelineType {
+    fn from(timeline_type: spTimelineType) -> Self {
+        match timeline_type {
+            0 => Self::Attachment,
+            1 => Self::Alpha,
+            2 => Self::PathConstraintPosition,
+            3 => Self::PathConstraintSpacing,
+            4 => Self::Rotate,
+            5 => Self::ScaleX,
+            6 => Self::ScaleY,
+            7 => Self::ShearX,
+            8 => Self::ShearY,
+            9 => Self::TranslateX,
+            10 => Self::TranslateY,
+            11 => Self::Scale,
+            12 => Self::Shear,
+            13 => Self::Translate,
+            14 => Self::Deform,
+            15 => Self::Sequence,
+            16 => Self::IkConstraint,
+            17 => Self::PathConstraintMix,
+            18 => Self::Rgb2,
+            19 => Self::Rgba2,
+            20 => Self::Rgba,
+            21 => Self::Rgb,
+            22 => Self::TransformConstraint,
+            23 => Self::DrawOrder,
+            24 => Self::Event,
+            _ => Self::Unknown,
+        }
+    }
+}
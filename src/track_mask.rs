@@ -0,0 +1,451 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+    c::{
+        c_float, c_int, spAnimation, spBoneData, spEvent, spMixBlend, spMixDirection, spSkeleton,
+        spTimeline, spTimelineArray, spTimeline_apply, SP_TIMELINE_ATTACHMENT, SP_TIMELINE_EVENT,
+        SP_TIMELINE_ROTATE, SP_TIMELINE_SHEARX,
+    },
+    c_interface::NewFromPtr,
+    error::SpineError,
+    timeline::{Timeline, TimelineType},
+    SkeletonData,
+};
+
+/// Restricts which bones and slots are affected by a track of an
+/// [`AnimationState`](`crate::AnimationState`), see
+/// [`AnimationState::set_track_mask`](`crate::AnimationState::set_track_mask`).
+///
+/// Every bone starts with the default weight (0 unless set with
+/// [`with_default_weight`](`Self::with_default_weight`)), then each entry is applied in the order
+/// it was added, so later entries override earlier ones. Slots use the weight of their bone
+/// unless given a weight of their own.
+///
+/// A weight of 0 filters out all timelines keying the bone or slot, a weight of 1 applies them
+/// as usual, and weights in between scale the alpha of the timelines. Attachment and sequence
+/// changes cannot be partially applied, so they are applied fully for any weight above 0.
+/// Constraint timelines use the highest weight of the bones they constrain. Event and draw order
+/// timelines are never filtered.
+///
+/// ```
+/// # #[path="./test.rs"]
+/// # mod test;
+/// # use rusty_spine::TrackMask;
+/// # let (skeleton, mut animation_state) = test::TestAsset::spineboy().instance();
+/// // Only let track 1 affect the upper body
+/// animation_state
+///     .set_track_mask(1, &TrackMask::new().with_bone_tree("torso", 1.))
+///     .unwrap();
+/// animation_state.set_animation_by_name(1, "shoot", false).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackMask {
+    default_weight: f32,
+    entries: Vec<TrackMaskEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum TrackMaskEntry {
+    Bone { name: String, weight: f32 },
+    BoneTree { name: String, weight: f32 },
+    Slot { name: String, weight: f32 },
+}
+
+impl TrackMask {
+    /// Create a mask which filters out every bone and slot.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the weight of all bones not covered by an entry, use 1 to create a mask which only
+    /// excludes some bones.
+    #[must_use]
+    pub const fn with_default_weight(mut self, weight: f32) -> Self {
+        self.default_weight = weight;
+        self
+    }
+
+    /// Set the weight of a single bone.
+    #[must_use]
+    pub fn with_bone(mut self, name: &str, weight: f32) -> Self {
+        self.entries.push(TrackMaskEntry::Bone {
+            name: name.to_owned(),
+            weight,
+        });
+        self
+    }
+
+    /// Set the weight of a bone and all of its descendants.
+    #[must_use]
+    pub fn with_bone_tree(mut self, root_name: &str, weight: f32) -> Self {
+        self.entries.push(TrackMaskEntry::BoneTree {
+            name: root_name.to_owned(),
+            weight,
+        });
+        self
+    }
+
+    /// Set the weight of a single slot, overriding the weight of its bone.
+    #[must_use]
+    pub fn with_slot(mut self, name: &str, weight: f32) -> Self {
+        self.entries.push(TrackMaskEntry::Slot {
+            name: name.to_owned(),
+            weight,
+        });
+        self
+    }
+
+    pub(crate) fn resolve(&self, skeleton_data: &SkeletonData) -> Result<ResolvedMask, SpineError> {
+        let mut bones = vec![self.default_weight; skeleton_data.bones_count()];
+        let mut slots = vec![None; skeleton_data.slots_count()];
+        for entry in &self.entries {
+            match entry {
+                TrackMaskEntry::Bone { name, weight } => {
+                    let bone = skeleton_data
+                        .find_bone(name)
+                        .ok_or_else(|| SpineError::new_not_found("Bone", name))?;
+                    bones[bone.index()] = *weight;
+                }
+                TrackMaskEntry::BoneTree { name, weight } => {
+                    let root = skeleton_data
+                        .find_bone(name)
+                        .ok_or_else(|| SpineError::new_not_found("Bone", name))?;
+                    for bone in skeleton_data.bones() {
+                        let mut ancestor = Some(bone.c_ptr());
+                        while let Some(ptr) = ancestor {
+                            if ptr == root.c_ptr() {
+                                bones[bone.index()] = *weight;
+                                break;
+                            }
+                            ancestor = Some(unsafe { (*ptr).parent }).filter(|ptr| !ptr.is_null());
+                        }
+                    }
+                }
+                TrackMaskEntry::Slot { name, weight } => {
+                    let slot = skeleton_data
+                        .find_slot(name)
+                        .ok_or_else(|| SpineError::new_not_found("Slot", name))?;
+                    slots[slot.index()] = Some(*weight);
+                }
+            }
+        }
+        let slots = skeleton_data
+            .slots()
+            .zip(slots)
+            .map(|(slot, weight)| weight.unwrap_or(bones[slot.bone_data().index()]))
+            .collect();
+        let c_skeleton_data = unsafe { &*skeleton_data.c_ptr() };
+        let constraint_weight = |bones_count: c_int, constraint_bones: *mut *mut spBoneData| {
+            (0..bones_count as usize)
+                .map(|index| unsafe {
+                    let bone = *constraint_bones.add(index);
+                    bones[(*bone).index as usize]
+                })
+                .fold(0., f32::max)
+        };
+        let ik_constraints = (0..c_skeleton_data.ikConstraintsCount as usize)
+            .map(|index| unsafe {
+                let data = *c_skeleton_data.ikConstraints.add(index);
+                constraint_weight((*data).bonesCount, (*data).bones)
+            })
+            .collect();
+        let transform_constraints = (0..c_skeleton_data.transformConstraintsCount as usize)
+            .map(|index| unsafe {
+                let data = *c_skeleton_data.transformConstraints.add(index);
+                constraint_weight((*data).bonesCount, (*data).bones)
+            })
+            .collect();
+        let path_constraints = (0..c_skeleton_data.pathConstraintsCount as usize)
+            .map(|index| unsafe {
+                let data = *c_skeleton_data.pathConstraints.add(index);
+                constraint_weight((*data).bonesCount, (*data).bones)
+            })
+            .collect();
+        Ok(ResolvedMask {
+            bones,
+            slots,
+            ik_constraints,
+            transform_constraints,
+            path_constraints,
+        })
+    }
+}
+
+/// A [`TrackMask`] resolved to weights by index for a specific [`SkeletonData`].
+#[derive(Debug)]
+pub(crate) struct ResolvedMask {
+    bones: Vec<f32>,
+    slots: Vec<f32>,
+    ik_constraints: Vec<f32>,
+    transform_constraints: Vec<f32>,
+    path_constraints: Vec<f32>,
+}
+
+impl ResolvedMask {
+    fn timeline_weight(&self, timeline: &Timeline) -> f32 {
+        let weights = match timeline.timeline_type() {
+            TimelineType::IkConstraint => &self.ik_constraints,
+            TimelineType::TransformConstraint => &self.transform_constraints,
+            TimelineType::PathConstraintPosition
+            | TimelineType::PathConstraintSpacing
+            | TimelineType::PathConstraintMix => &self.path_constraints,
+            timeline_type if timeline_type.is_bone_timeline() => &self.bones,
+            _ => &self.slots,
+        };
+        timeline
+            .bone_index()
+            .or_else(|| timeline.slot_index())
+            .or_else(|| timeline.constraint_index())
+            .and_then(|index| weights.get(index))
+            .copied()
+            .unwrap_or(1.)
+            .clamp(0., 1.)
+    }
+}
+
+/// A timeline which applies another timeline with its alpha scaled by a weight.
+#[repr(C)]
+struct MaskedTimeline {
+    super_0: spTimeline,
+    inner: *mut spTimeline,
+    weight: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn masked_timeline_apply(
+    timeline: *mut spTimeline,
+    skeleton: *mut spSkeleton,
+    last_time: c_float,
+    time: c_float,
+    fired_events: *mut *mut spEvent,
+    events_count: *mut c_int,
+    alpha: c_float,
+    blend: spMixBlend,
+    direction: spMixDirection,
+) {
+    let masked = &*timeline.cast::<MaskedTimeline>();
+    if masked.weight > 0. {
+        spTimeline_apply(
+            masked.inner,
+            skeleton,
+            last_time,
+            time,
+            fired_events,
+            events_count,
+            alpha * masked.weight,
+            blend,
+            direction,
+        );
+    }
+}
+
+/// A copy of an animation with its timelines filtered by a [`ResolvedMask`].
+///
+/// It has the same number of timelines in the same order as the original animation, because the
+/// animation state keeps per-timeline data for each track entry. The timelines are borrowed from
+/// the original animation, which must outlive this.
+pub(crate) struct MaskedAnimation {
+    c_animation: Box<spAnimation>,
+    _c_timelines: Box<spTimelineArray>,
+    _timelines: Vec<*mut spTimeline>,
+    // Boxed so the pointers in `_timelines` stay valid while this grows.
+    #[allow(clippy::vec_box)]
+    _masked_timelines: Vec<Box<MaskedTimeline>>,
+}
+
+impl MaskedAnimation {
+    pub(crate) unsafe fn new(c_animation: *mut spAnimation, mask: &ResolvedMask) -> Self {
+        let original = &*c_animation;
+        let count = (*original.timelines).size as usize;
+        let mut timelines = Vec::with_capacity(count);
+        let mut masked_timelines = vec![];
+        for index in 0..count {
+            let c_timeline = *(*original.timelines).items.add(index);
+            let timeline = Timeline::new_from_ptr(c_timeline);
+            let weight = mask.timeline_weight(&timeline);
+            let discrete = matches!(
+                timeline.timeline_type(),
+                TimelineType::Attachment | TimelineType::Sequence
+            );
+            if weight >= 1. || (discrete && weight > 0.) {
+                timelines.push(c_timeline);
+                continue;
+            }
+            let mut super_0 = *c_timeline;
+            super_0.vtable.apply = Some(masked_timeline_apply);
+            super_0.vtable.dispose = None;
+            super_0.vtable.setBezier = None;
+            // The animation state casts rotate and attachment timelines to their concrete types,
+            // so the wrapper must not claim to be one. Attachment wrappers become event timelines,
+            // which are mixed the same way. Rotate wrappers become a plain property timeline so
+            // they keep their hold mixing, but are mixed without the shortest rotation.
+            if super_0.type_0 == SP_TIMELINE_ROTATE {
+                super_0.type_0 = SP_TIMELINE_SHEARX;
+            } else if super_0.type_0 == SP_TIMELINE_ATTACHMENT {
+                super_0.type_0 = SP_TIMELINE_EVENT;
+            }
+            let mut masked_timeline = Box::new(MaskedTimeline {
+                super_0,
+                inner: c_timeline,
+                weight,
+            });
+            timelines.push(std::ptr::addr_of_mut!(masked_timeline.super_0));
+            masked_timelines.push(masked_timeline);
+        }
+        let mut c_timelines = Box::new(spTimelineArray {
+            size: count as c_int,
+            capacity: count as c_int,
+            items: timelines.as_mut_ptr(),
+        });
+        let c_animation = Box::new(spAnimation {
+            name: original.name,
+            duration: original.duration,
+            timelines: std::ptr::addr_of_mut!(*c_timelines),
+            timelineIds: original.timelineIds,
+        });
+        Self {
+            c_animation,
+            _c_timelines: c_timelines,
+            _timelines: timelines,
+            _masked_timelines: masked_timelines,
+        }
+    }
+
+    pub(crate) fn c_ptr(&mut self) -> *mut spAnimation {
+        std::ptr::addr_of_mut!(*self.c_animation)
+    }
+}
+
+/// The mask of a single track and the masked animations it has been applied to so far.
+///
+/// The masked animations are created while applying, which only borrows the animation state, so
+/// they are kept behind a lock.
+pub(crate) struct TrackMaskState {
+    pub(crate) mask: TrackMask,
+    pub(crate) resolved: ResolvedMask,
+    pub(crate) animations: Mutex<HashMap<*mut spAnimation, MaskedAnimation>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test::TestAsset, AnimationState, Skeleton};
+
+    use super::*;
+
+    fn apply_run(mask: Option<&TrackMask>) -> Skeleton {
+        let (mut skeleton, mut animation_state) = TestAsset::spineboy().instance();
+        if let Some(mask) = mask {
+            animation_state.set_track_mask(0, mask).unwrap();
+        }
+        let _ = animation_state.set_animation_by_name(0, "run", true);
+        animation_state.update(0.3);
+        animation_state.apply(&mut skeleton);
+        skeleton
+    }
+
+    #[test]
+    fn animation_timelines() {
+        let skeleton_data = TestAsset::spineboy().skeleton_data();
+        let run = skeleton_data.find_animation("run").unwrap();
+        assert!(run.timelines_count() > 0);
+        assert_eq!(run.timelines().count(), run.timelines_count());
+        let torso = skeleton_data.find_bone("torso").unwrap().index();
+        assert!(run
+            .timelines()
+            .any(|timeline| timeline.timeline_type() == TimelineType::Rotate
+                && timeline.bone_index() == Some(torso)));
+        for timeline in run.timelines() {
+            if let Some(bone_index) = timeline.bone_index() {
+                assert!(bone_index < skeleton_data.bones_count());
+            }
+            if let Some(slot_index) = timeline.slot_index() {
+                assert!(slot_index < skeleton_data.slots_count());
+            }
+        }
+    }
+
+    #[test]
+    fn track_mask_bone_tree() {
+        let skeleton_data = TestAsset::spineboy().skeleton_data();
+        let torso = skeleton_data.find_bone("torso").unwrap().c_ptr();
+        let unmasked = apply_run(None);
+        let masked = apply_run(Some(&TrackMask::new().with_bone_tree("torso", 1.)));
+        for ((masked, unmasked), data) in masked
+            .bones()
+            .zip(unmasked.bones())
+            .zip(skeleton_data.bones())
+        {
+            let mut in_tree = false;
+            let mut ancestor = data.c_ptr();
+            while !ancestor.is_null() {
+                in_tree |= ancestor == torso;
+                ancestor = unsafe { (*ancestor).parent };
+            }
+            let expected = if in_tree {
+                (unmasked.rotation(), unmasked.x(), unmasked.y())
+            } else {
+                (data.rotation(), data.x(), data.y())
+            };
+            assert_eq!((masked.rotation(), masked.x(), masked.y()), expected);
+        }
+    }
+
+    #[test]
+    fn track_mask_weight() {
+        let skeleton_data = TestAsset::spineboy().skeleton_data();
+        let unmasked = apply_run(None);
+        let masked = apply_run(Some(&TrackMask::new().with_bone("front-thigh", 0.5)));
+        let index = skeleton_data.find_bone("front-thigh").unwrap().index();
+        let setup = skeleton_data.bone_at_index(index).unwrap().rotation();
+        let full = unmasked.bone_at_index(index).unwrap().rotation();
+        let half = masked.bone_at_index(index).unwrap().rotation();
+        assert!((half - (setup + full) / 2.).abs() < 1e-3);
+    }
+
+    /// Masked timelines which are also keyed by the animation being mixed to are held, like
+    /// unmasked ones.
+    #[test]
+    fn track_mask_hold_mixing() {
+        let mix = |mask: Option<&TrackMask>| {
+            let (mut skeleton, mut animation_state) = TestAsset::spineboy().instance();
+            if let Some(mask) = mask {
+                animation_state.set_track_mask(0, mask).unwrap();
+            }
+            let _ = animation_state.set_animation_by_name(0, "run", true);
+            animation_state.update(0.3);
+            animation_state.apply(&mut skeleton);
+            let mut track_entry = animation_state
+                .set_animation_by_name(0, "walk", true)
+                .unwrap();
+            track_entry.set_mix_duration(1.);
+            animation_state.update(0.5);
+            animation_state.apply(&mut skeleton);
+            skeleton
+        };
+        let unmasked = mix(None);
+        let masked = mix(Some(&TrackMask::new().with_default_weight(0.999)));
+        for (masked, unmasked) in masked.bones().zip(unmasked.bones()) {
+            assert!((masked.rotation() - unmasked.rotation()).abs() < 1.);
+            assert!((masked.x() - unmasked.x()).abs() < 1.);
+            assert!((masked.y() - unmasked.y()).abs() < 1.);
+        }
+    }
+
+    #[test]
+    fn track_mask_not_found() {
+        let (_, animation_state_data) = TestAsset::spineboy().instance_data();
+        let mut animation_state = AnimationState::new(animation_state_data);
+        assert!(animation_state
+            .set_track_mask(0, &TrackMask::new().with_bone("tail", 1.))
+            .is_err());
+        assert!(animation_state.track_mask(0).is_none());
+        let mask = TrackMask::new().with_slot("head", 1.);
+        animation_state.set_track_mask(0, &mask).unwrap();
+        assert_eq!(animation_state.track_mask(0), Some(&mask));
+        animation_state.clear_track_mask(0);
+        assert!(animation_state.track_mask(0).is_none());
+    }
+}
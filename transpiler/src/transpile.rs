@@ -140,12 +140,45 @@ pub fn fix_source(name: &str, mut src: String) -> String {
         src
     } else if name == "AnimationState.c" {
         src = replace_identifier(src, "binarySearch1", "binarySearch1_state", 0);
+        src = split_empty_animation_init(src);
         src
     } else {
         src
     }
 }
 
+/// Move the lazy creation of the empty animation out of `spAnimationState_create` into a new
+/// `spAnimationState_initStatics`, so it can be created once before any animation state.
+pub fn split_empty_animation_init(src: String) -> String {
+    let init_start = src
+        .find("if (!SP_EMPTY_ANIMATION) {")
+        .expect("empty animation init not found");
+    let mut depth = 0;
+    let mut init_end = init_start;
+    for (index, c) in src[init_start..].char_indices() {
+        if c == '{' {
+            depth += 1;
+        } else if c == '}' {
+            depth -= 1;
+            if depth == 0 {
+                init_end = init_start + index + 1;
+                break;
+            }
+        }
+    }
+    let create_start = src[..init_start]
+        .rfind("spAnimationState_create")
+        .and_then(|index| src[..index].rfind('\n'))
+        .map_or(0, |index| index + 1);
+    String::from(&src[..create_start])
+        + "void spAnimationState_initStatics() {\n\t"
+        + &src[init_start..init_end]
+        + "\n}\n\n"
+        + &src[create_start..init_start]
+        + "spAnimationState_initStatics();"
+        + &src[init_end..]
+}
+
 pub fn c_fixes_before_preprocessor(input: &str, output: &str) {
     let mut src = read_to_string(input).unwrap();
     src = src.replace("isspace", "isspace_");